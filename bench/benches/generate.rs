@@ -1,6 +1,14 @@
 use criterion::{criterion_group, criterion_main, Criterion};
-use std::collections::HashMap;
-use tinyjson::JsonValue;
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use tinyjson::{JsonGenerator, JsonValue};
+use tinyjson_bench::array_of_objects;
+
+// Unbuffered file where each write is a system call
+fn null_device() -> File {
+    let path = if cfg!(windows) { "NUL" } else { "/dev/null" };
+    OpenOptions::new().write(true).open(path).unwrap()
+}
 
 fn generate(c: &mut Criterion) {
     c.bench_function("generate::string", |b| {
@@ -17,6 +25,22 @@ fn generate(c: &mut Criterion) {
             assert_eq!(n, "123.456");
         });
     });
+    c.bench_function("generate::numbers", |b| {
+        let value = JsonValue::from(
+            (0..1000)
+                .map(|i| match i % 4 {
+                    0 => JsonValue::from(i as f64),
+                    1 => JsonValue::from(-(i as f64) * 1234567.0),
+                    2 => JsonValue::from(i as f64 * 0.37 + 0.001),
+                    _ => JsonValue::from(i as f64 * 1.7e-9),
+                })
+                .collect::<Vec<_>>(),
+        );
+        b.iter(|| {
+            let s = value.stringify().unwrap();
+            assert!(s.starts_with("[0,-1234567,0.741,5.099999999999999e-9,4,"));
+        });
+    });
     c.bench_function("generate::bool", |b| {
         let value = JsonValue::from(true);
         b.iter(|| {
@@ -36,7 +60,7 @@ fn generate(c: &mut Criterion) {
         });
     });
     c.bench_function("generate::object", |b| {
-        let mut kv = HashMap::new();
+        let mut kv = BTreeMap::new();
         kv.insert("num".into(), 123.45.into());
         kv.insert("bool".into(), true.into());
         kv.insert("str".into(), "this is test".to_string().into());
@@ -50,5 +74,56 @@ fn generate(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, generate);
+// Generate with default options to check the path without any option is not slower
+fn generate_default(c: &mut Criterion) {
+    let value: JsonValue = array_of_objects(1000).parse().unwrap();
+    c.bench_function("generate::default::stringify", |b| {
+        b.iter(|| {
+            let s = value.stringify().unwrap();
+            assert!(s.starts_with(r#"[{"created_at":"2020-12-01","#));
+        });
+    });
+    c.bench_function("generate::default::format", |b| {
+        b.iter(|| {
+            let s = value.format().unwrap();
+            assert!(s.starts_with("[\n  {\n    \"created_at\": \"2020-12-01\","));
+        });
+    });
+    let value = JsonValue::from(
+        (0..10000)
+            .map(|i| match i % 3 {
+                0 => JsonValue::from(true),
+                1 => JsonValue::Null,
+                _ => JsonValue::from(i as f64),
+            })
+            .collect::<Vec<_>>(),
+    );
+    c.bench_function("generate::default::literals", |b| {
+        b.iter(|| {
+            let s = value.stringify().unwrap();
+            assert!(s.starts_with("[true,null,2,true,"));
+        });
+    });
+}
+
+fn generate_to_file(c: &mut Criterion) {
+    let value: JsonValue = array_of_objects(1000).parse().unwrap();
+    c.bench_function("generate::file::buffered", |b| {
+        let mut file = null_device();
+        b.iter(|| {
+            JsonGenerator::new(&mut file)
+                .buffer_size(8 * 1024)
+                .generate(&value)
+                .unwrap();
+        });
+    });
+    c.bench_function("generate::file::unbuffered", |b| {
+        let mut file = null_device();
+        b.iter(|| {
+            JsonGenerator::new(&mut file).generate(&value).unwrap();
+        });
+    });
+}
+
+criterion_group!(benches, generate, generate_default, generate_to_file);
 criterion_main!(benches);
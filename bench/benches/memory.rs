@@ -0,0 +1,55 @@
+// Measure the number of allocations and the total bytes allocated while parsing. This is not a criterion benchmark
+// since criterion measures only time.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tinyjson::{JsonParser, JsonValue, KeyInterner};
+use tinyjson_bench::array_of_objects;
+
+struct Counter;
+
+static COUNT: AtomicUsize = AtomicUsize::new(0);
+static BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counter {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        COUNT.fetch_add(1, Ordering::Relaxed);
+        BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        COUNT.fetch_add(1, Ordering::Relaxed);
+        BYTES.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counter = Counter;
+
+fn measure<T, F: FnOnce() -> T>(name: &str, f: F) {
+    let (count, bytes) = (COUNT.load(Ordering::Relaxed), BYTES.load(Ordering::Relaxed));
+    let value = f();
+    let count = COUNT.load(Ordering::Relaxed) - count;
+    let bytes = BYTES.load(Ordering::Relaxed) - bytes;
+    println!("{}: {} allocations, {} bytes", name, count, bytes);
+    drop(value);
+}
+
+fn main() {
+    let s = array_of_objects(100000);
+    measure("parse::array_of_objects", || {
+        s.parse::<JsonValue>().unwrap()
+    });
+    measure("parse::array_of_objects_interned", || {
+        let mut keys = KeyInterner::new();
+        let value = JsonParser::new(s.chars())
+            .parse_interned(&mut keys)
+            .unwrap();
+        (keys, value)
+    });
+}
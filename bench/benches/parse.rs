@@ -1,7 +1,7 @@
 use criterion::{criterion_group, criterion_main, Criterion};
 use std::convert::TryInto;
-use tinyjson::JsonValue;
-use tinyjson_bench::load_my_2020_dec_tweets;
+use tinyjson::{InternedJsonValue, JsonParser, JsonValue, KeyInterner};
+use tinyjson_bench::{array_of_coordinates, array_of_objects, load_my_2020_dec_tweets};
 
 fn parse(c: &mut Criterion) {
     c.bench_function("parse::string", |b| {
@@ -45,6 +45,32 @@ fn parse(c: &mut Criterion) {
             assert!(matches!(value, JsonValue::Object(_)));
         });
     });
+    c.bench_function("parse::array_of_objects", |b| {
+        let s = array_of_objects(1000);
+        b.iter(|| {
+            let value: JsonValue = s.parse().unwrap();
+            let objects: Vec<_> = value.try_into().unwrap();
+            assert_eq!(objects.len(), 1000);
+        });
+    });
+    c.bench_function("parse::array_of_objects_interned", |b| {
+        let s = array_of_objects(1000);
+        b.iter(|| {
+            let mut keys = KeyInterner::new();
+            let value = JsonParser::new(s.chars())
+                .parse_interned(&mut keys)
+                .unwrap();
+            assert!(matches!(value, InternedJsonValue::Array(a) if a.len() == 1000));
+        });
+    });
+    c.bench_function("parse::array_of_coordinates", |b| {
+        let s = array_of_coordinates(1000);
+        b.iter(|| {
+            let value: JsonValue = s.parse().unwrap();
+            let coordinates: Vec<_> = value.try_into().unwrap();
+            assert_eq!(coordinates.len(), 1000);
+        });
+    });
     c.bench_function("parse::my_tweets_2020_dec", |b| {
         let s = load_my_2020_dec_tweets();
         b.iter(|| {
@@ -53,6 +79,15 @@ fn parse(c: &mut Criterion) {
             assert_eq!(tweets.len(), 217);
         });
     });
+    c.bench_function("parse::my_tweets_2020_dec_into", |b| {
+        let s = load_my_2020_dec_tweets();
+        let mut value = JsonValue::Null;
+        b.iter(|| {
+            JsonParser::new(s.chars()).parse_into(&mut value).unwrap();
+            let tweets: &Vec<_> = value.get().unwrap();
+            assert_eq!(tweets.len(), 217);
+        });
+    });
 }
 
 criterion_group!(benches, parse);
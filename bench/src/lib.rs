@@ -6,3 +6,32 @@ pub fn load_my_2020_dec_tweets() -> String {
     p.push("tweets_2020_dec.json");
     fs::read_to_string(p).unwrap()
 }
+
+pub fn array_of_objects(len: usize) -> String {
+    let mut s = String::from("[");
+    for i in 0..len {
+        if i > 0 {
+            s.push(',');
+        }
+        s.push_str(&format!(
+            r#"{{"id":{},"name":"user{}","screen_name":"user_{}","created_at":"2020-12-01","followers_count":{},"verified":false,"profile_image_url":null}}"#,
+            i, i, i, i * 3,
+        ));
+    }
+    s.push(']');
+    s
+}
+
+pub fn array_of_coordinates(len: usize) -> String {
+    let mut s = String::from("[");
+    for i in 0..len {
+        if i > 0 {
+            s.push(',');
+        }
+        let lon = 139.0 + i as f64 * 0.000123;
+        let lat = 35.0 - i as f64 * 0.000077;
+        s.push_str(&format!("[{:.6},{:.6},{}]", lon, lat, i % 100));
+    }
+    s.push(']');
+    s
+}
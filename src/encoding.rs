@@ -0,0 +1,168 @@
+use crate::parser::{JsonParseError, JsonParseErrorKind, JsonParseResult, JsonParser};
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::String;
+use core::char;
+use core::str;
+
+/// Text encoding of JSON byte sequence. See [`Encoding::detect`] to know how the encoding is detected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// UTF-8 encoding.
+    Utf8,
+    /// UTF-16 little endian encoding.
+    Utf16Le,
+    /// UTF-16 big endian encoding.
+    Utf16Be,
+    /// UTF-32 little endian encoding.
+    Utf32Le,
+    /// UTF-32 big endian encoding.
+    Utf32Be,
+}
+
+impl Encoding {
+    /// Detect the encoding of the given byte sequence. It returns the detected encoding and the length of BOM in
+    /// bytes. When the sequence starts with BOM, the encoding is decided by the BOM. Otherwise it is guessed from the
+    /// pattern of null bytes in the first 4 bytes as described in [RFC 4627](https://www.rfc-editor.org/rfc/rfc4627#section-3)
+    /// since the first two characters of JSON text are always ASCII characters.
+    ///
+    /// ```
+    /// use tinyjson::Encoding;
+    ///
+    /// assert_eq!(Encoding::detect(b"\xef\xbb\xbf[]"), (Encoding::Utf8, 3));
+    /// assert_eq!(Encoding::detect(b"\xff\xfe[\x00]\x00"), (Encoding::Utf16Le, 2));
+    /// assert_eq!(Encoding::detect(b"\x00[\x00]"), (Encoding::Utf16Be, 0));
+    /// assert_eq!(Encoding::detect(b"[]"), (Encoding::Utf8, 0));
+    /// ```
+    pub fn detect(bytes: &[u8]) -> (Encoding, usize) {
+        match bytes {
+            [0xef, 0xbb, 0xbf, ..] => (Encoding::Utf8, 3),
+            [0x00, 0x00, 0xfe, 0xff, ..] => (Encoding::Utf32Be, 4),
+            [0xff, 0xfe, 0x00, 0x00, ..] => (Encoding::Utf32Le, 4),
+            [0xfe, 0xff, ..] => (Encoding::Utf16Be, 2),
+            [0xff, 0xfe, ..] => (Encoding::Utf16Le, 2),
+            [0x00, 0x00, 0x00, _, ..] => (Encoding::Utf32Be, 0),
+            [_, 0x00, 0x00, 0x00, ..] => (Encoding::Utf32Le, 0),
+            [0x00, _, ..] => (Encoding::Utf16Be, 0),
+            [_, 0x00, ..] => (Encoding::Utf16Le, 0),
+            _ => (Encoding::Utf8, 0),
+        }
+    }
+}
+
+// Calculate the position of the character following the successfully decoded prefix in the same manner as parser
+fn decode_error<T>(msg: String, decoded: &str) -> Result<T, JsonParseError> {
+    let mut line = 1;
+    let mut col = 1;
+    for c in decoded.chars() {
+        if c == '\n' {
+            line += 1;
+            col = 1;
+        } else {
+            col += 1;
+        }
+    }
+    Err(JsonParseError::with_kind(
+        JsonParseErrorKind::Encoding,
+        msg,
+        line,
+        col,
+    ))
+}
+
+pub(crate) fn decode_utf8(bytes: &[u8]) -> Result<&str, JsonParseError> {
+    str::from_utf8(bytes).or_else(|err| {
+        let valid = str::from_utf8(&bytes[..err.valid_up_to()]).unwrap();
+        decode_error(format!("Invalid UTF-8 byte sequence: {}", err), valid)
+    })
+}
+
+fn decode_utf16(bytes: &[u8], read: fn([u8; 2]) -> u16) -> Result<String, JsonParseError> {
+    let mut s = String::with_capacity(bytes.len() / 2);
+    let chunks = bytes.chunks_exact(2);
+    let truncated = !chunks.remainder().is_empty();
+    for c in char::decode_utf16(chunks.map(|b| read([b[0], b[1]]))) {
+        match c {
+            Ok(c) => s.push(c),
+            Err(err) => return decode_error(format!("Invalid UTF-16 byte sequence: {}", err), &s),
+        }
+    }
+    if truncated {
+        return decode_error(String::from("UTF-16 byte sequence is truncated"), &s);
+    }
+    Ok(s)
+}
+
+fn decode_utf32(bytes: &[u8], read: fn([u8; 4]) -> u32) -> Result<String, JsonParseError> {
+    let mut s = String::with_capacity(bytes.len() / 4);
+    let chunks = bytes.chunks_exact(4);
+    let truncated = !chunks.remainder().is_empty();
+    for b in chunks {
+        let u = read([b[0], b[1], b[2], b[3]]);
+        match char::from_u32(u) {
+            Some(c) => s.push(c),
+            None => return decode_error(format!("Invalid UTF-32 code point 0x{:x}", u), &s),
+        }
+    }
+    if truncated {
+        return decode_error(String::from("UTF-32 byte sequence is truncated"), &s);
+    }
+    Ok(s)
+}
+
+/// Decode the given JSON byte sequence into UTF-8 string. The encoding is detected by [`Encoding::detect`] and BOM is
+/// removed from the result. UTF-8 input without BOM is returned without copying.
+///
+/// This is useful when you want to parse byte sequence with some customized [`JsonParser`].
+///
+/// ```
+/// use tinyjson::decode_bytes;
+///
+/// let s = decode_bytes(b"\xff\xfe[\x001\x00]\x00").unwrap();
+/// assert_eq!(s, "[1]");
+/// ```
+pub fn decode_bytes(bytes: &[u8]) -> Result<Cow<'_, str>, JsonParseError> {
+    let (enc, bom) = Encoding::detect(bytes);
+    let bytes = &bytes[bom..];
+    match enc {
+        Encoding::Utf8 => decode_utf8(bytes).map(Cow::Borrowed),
+        Encoding::Utf16Le => decode_utf16(bytes, u16::from_le_bytes).map(Cow::Owned),
+        Encoding::Utf16Be => decode_utf16(bytes, u16::from_be_bytes).map(Cow::Owned),
+        Encoding::Utf32Le => decode_utf32(bytes, u32::from_le_bytes).map(Cow::Owned),
+        Encoding::Utf32Be => decode_utf32(bytes, u32::from_be_bytes).map(Cow::Owned),
+    }
+}
+
+/// Parse the given byte sequence into `JsonValue` value. UTF-8 with or without BOM, UTF-16 and UTF-32 are accepted.
+/// See [`decode_bytes`] for more details.
+///
+/// ```
+/// use tinyjson::JsonValue;
+///
+/// // UTF-8 with BOM
+/// let v = tinyjson::parse_bytes(b"\xef\xbb\xbf[1, 2]").unwrap();
+/// assert!(v.is_array());
+///
+/// // UTF-16LE without BOM
+/// let v = tinyjson::parse_bytes(b"\"\x00\xac\x20\"\x00").unwrap();
+/// assert_eq!(v, JsonValue::from("\u{20ac}".to_string()));
+/// ```
+pub fn parse_bytes(bytes: &[u8]) -> JsonParseResult {
+    let s = decode_bytes(bytes)?;
+    JsonParser::new(s.chars()).parse()
+}
+
+/// Parse the given byte sequence into `JsonValue` value strictly. Unlike [`parse_bytes`], only UTF-8 without BOM is
+/// accepted as required by [RFC 8259](https://www.rfc-editor.org/rfc/rfc8259#section-8.1).
+///
+/// ```
+/// let v = tinyjson::parse_bytes_strict(b"[1, 2]").unwrap();
+/// assert!(v.is_array());
+///
+/// // BOM is rejected
+/// assert!(tinyjson::parse_bytes_strict(b"\xef\xbb\xbf[1, 2]").is_err());
+/// ```
+pub fn parse_bytes_strict(bytes: &[u8]) -> JsonParseResult {
+    let s = decode_utf8(bytes)?;
+    JsonParser::new(s.chars()).parse()
+}
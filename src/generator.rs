@@ -1,32 +1,745 @@
-use crate::JsonValue;
-use std::collections::HashMap;
-use std::fmt;
-use std::io::{self, Write};
+use crate::json_value::JsonValue;
+use crate::redaction::{Redaction, RedactionState, Segment};
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt::{self, Write as _};
+use core::mem;
+use core::str;
+#[cfg(feature = "std")]
+use std::io;
 
-/// Serialization error. This error only happens when some write error happens on writing the serialized byte sequence
-/// to the given `io::Write` object.
-#[derive(Debug)]
-pub struct JsonGenerateError {
-    msg: String,
+/// Kind of serialization error. See [`JsonGenerateError::kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum JsonGenerateErrorKind {
+    /// Writing the output failed.
+    Io,
+    /// The value cannot be represented in JSON such as NaN. See [`NonFinitePolicy`].
+    InvalidValue,
+    /// Methods of [`crate::JsonWriter`] were called in wrong order.
+    InvalidSequence,
+}
+
+// Element of the path to the value where the error happened
+#[derive(Debug, Clone)]
+enum PathSegment {
+    Index(usize),
+    Key(String),
+}
+
+/// Serialization error. This error happens when writing the output failed or when the value cannot be represented in
+/// JSON. The path to the value where the error happened is recorded.
+///
+/// ```
+/// use tinyjson::{JsonGenerateErrorKind, JsonValue};
+///
+/// let v: JsonValue = r#"{"series": [{"points": [1, 2]}]}"#.parse().unwrap();
+/// let mut v = v;
+/// v["series"][0]["points"][1] = f64::NAN.into();
+///
+/// let err = v.stringify().unwrap_err();
+/// assert_eq!(err.kind(), JsonGenerateErrorKind::InvalidValue);
+/// assert_eq!(err.path(), "$.series[0].points[1]");
+/// assert_eq!(
+///     err.to_string(),
+///     "Generate error: JSON cannot represent NaN at $.series[0].points[1]",
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct JsonGenerateError(Box<ErrorDetail>); // Boxed to keep `Result` returned from each write small
+
+#[derive(Debug, Clone)]
+struct ErrorDetail {
+    kind: JsonGenerateErrorKind,
+    msg: Cow<'static, str>,
+    path: Option<Vec<PathSegment>>, // From innermost to outermost. `None` means the path is not recorded
 }
 
 impl JsonGenerateError {
+    fn new(kind: JsonGenerateErrorKind, msg: &'static str) -> Self {
+        Self(Box::new(ErrorDetail {
+            kind,
+            msg: Cow::Borrowed(msg),
+            path: Some(Vec::new()),
+        }))
+    }
+
+    pub(crate) fn invalid_sequence(msg: &'static str) -> Self {
+        Self::new(JsonGenerateErrorKind::InvalidSequence, msg)
+    }
+
+    pub(crate) fn is_invalid_sequence(&self) -> bool {
+        self.0.kind == JsonGenerateErrorKind::InvalidSequence
+    }
+
+    fn with_segment(mut self, seg: impl FnOnce() -> PathSegment) -> Self {
+        if let Some(path) = &mut self.0.path {
+            path.push(seg());
+        }
+        self
+    }
+
+    fn at_index(self, idx: usize) -> Self {
+        self.with_segment(|| PathSegment::Index(idx))
+    }
+
+    fn at_key(self, key: &str) -> Self {
+        self.with_segment(|| PathSegment::Key(key.to_string()))
+    }
+
+    /// Error message without the path.
     pub fn message(&self) -> &str {
-        self.msg.as_str()
+        &self.0.msg
     }
+
+    /// Kind of the error.
+    pub fn kind(&self) -> JsonGenerateErrorKind {
+        self.0.kind
+    }
+
+    /// Path to the value where the error happened in JSONPath-like notation such as `$.series[12].points[3]`. `$`
+    /// means the root value.
+    pub fn path(&self) -> String {
+        let mut s = String::from("$");
+        for seg in self.0.path.iter().flatten().rev() {
+            match seg {
+                PathSegment::Index(idx) => {
+                    let _ = write!(s, "[{}]", idx);
+                }
+                PathSegment::Key(key) if is_identifier(key) => {
+                    s.push('.');
+                    s.push_str(key);
+                }
+                PathSegment::Key(key) => {
+                    s.push('[');
+                    let _ = JsonGenerator::new(FmtWriter(&mut s)).encode_string(key);
+                    s.push(']');
+                }
+            }
+        }
+        s
+    }
+}
+
+// Check the string is an ECMAScript identifier consisting of ASCII characters. Such keys are written as `.key` in
+// the path and written without quotes in JSON5
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '$' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
 }
 
 impl fmt::Display for JsonGenerateError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Generate error: {}", &self.msg)
+        write!(f, "Generate error: {}", &self.0.msg)?;
+        if self.0.kind == JsonGenerateErrorKind::InvalidValue {
+            write!(f, " at {}", self.path())?;
+        }
+        Ok(())
+    }
+}
+
+impl From<fmt::Error> for JsonGenerateError {
+    fn from(_: fmt::Error) -> Self {
+        Self::new(JsonGenerateErrorKind::Io, "Formatter returned an error")
+    }
+}
+
+/// Convert the error into `io::Error` of `InvalidData` kind so that `?` operator can be used in functions returning
+/// `io::Result`. The original error can be taken with `io::Error::get_ref` and `downcast_ref`.
+#[cfg(feature = "std")]
+impl From<JsonGenerateError> for io::Error {
+    fn from(err: JsonGenerateError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for JsonGenerateError {}
 
 /// Convenient type alias for serialization results.
 pub type JsonGenerateResult = Result<String, JsonGenerateError>;
 
+/// Output of [`JsonGenerator`]. The serialized JSON text is written to this object.
+///
+/// This trait is implemented for all `std::io::Write` types and for [`FmtWriter`] which wraps `core::fmt::Write` types.
+/// Implementations for `std::io::Write` types are available only when `std` feature is enabled.
+pub trait JsonWrite {
+    /// Error type returned from the generator.
+    type Error;
+
+    /// Write the given string to the output.
+    fn write_str(&mut self, s: &str) -> Result<(), Self::Error>;
+
+    /// Convert an error which is not caused by the output, such as a value which cannot be represented in JSON, into
+    /// the error type.
+    fn invalid_value(err: JsonGenerateError) -> Self::Error;
+}
+
+/// Errors which are not caused by the output are returned as `io::Error` of `InvalidData` kind wrapping
+/// [`JsonGenerateError`].
+///
+/// ```
+/// use std::io;
+/// use tinyjson::{JsonGenerateError, JsonValue};
+///
+/// let v = JsonValue::from(f64::NAN);
+/// let err = v.write_to(&mut vec![]).unwrap_err();
+/// assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+/// let err = err.get_ref().unwrap().downcast_ref::<JsonGenerateError>().unwrap();
+/// assert_eq!(err.message(), "JSON cannot represent NaN");
+/// ```
+#[cfg(feature = "std")]
+impl<W: io::Write> JsonWrite for W {
+    type Error = io::Error;
+
+    #[inline]
+    fn write_str(&mut self, s: &str) -> io::Result<()> {
+        self.write_all(s.as_bytes())
+    }
+
+    fn invalid_value(err: JsonGenerateError) -> io::Error {
+        err.into()
+    }
+}
+
+/// Wrapper to use a `core::fmt::Write` object such as `String` as an output of [`JsonGenerator`]. This is useful when
+/// `std` feature is disabled.
+///
+/// ```
+/// use tinyjson::{FmtWriter, JsonGenerator, JsonValue};
+///
+/// let v = JsonValue::from(vec![1.0.into(), 2.0.into(), 3.0.into()]);
+/// let mut s = String::new();
+/// JsonGenerator::new(FmtWriter(&mut s)).generate(&v).unwrap();
+/// assert_eq!(s, "[1,2,3]");
+/// ```
+pub struct FmtWriter<W: fmt::Write>(pub W);
+
+impl<W: fmt::Write> JsonWrite for FmtWriter<W> {
+    type Error = JsonGenerateError;
+
+    #[inline]
+    fn write_str(&mut self, s: &str) -> Result<(), JsonGenerateError> {
+        Ok(self.0.write_str(s)?)
+    }
+
+    fn invalid_value(err: JsonGenerateError) -> JsonGenerateError {
+        err
+    }
+}
+
+// Adapter to write formatted values to `JsonWrite` object
+struct FmtAdapter<'a, W: JsonWrite> {
+    out: &'a mut Buffered<W>,
+    error: Option<JsonGenerateError>,
+}
+
+impl<W: JsonWrite> fmt::Write for FmtAdapter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.out.write_str(s).map_err(|err| {
+            self.error = Some(err);
+            fmt::Error
+        })
+    }
+}
+
+/// Style of the output of [`JsonGenerator`]. Set it via [`JsonGenerator::style`]. The default style is the same as
+/// the output of [`JsonValue::format`] and [`JsonValue::stringify`].
+///
+/// ```
+/// use tinyjson::{FormatStyle, JsonGenerator, JsonValue};
+///
+/// let v: JsonValue = r#"{"a": [], "b": {}}"#.parse().unwrap();
+/// let style = FormatStyle::new()
+///     .space_before_colon(true)
+///     .crlf(true)
+///     .trailing_newline(true)
+///     .space_in_empty(true);
+/// let mut buf = vec![];
+/// JsonGenerator::new(&mut buf).indent("  ").style(style).generate(&v["a"]).unwrap();
+/// assert_eq!(String::from_utf8(buf).unwrap(), "[ ]\r\n");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatStyle {
+    space_before_colon: bool,
+    space_after_colon: bool,
+    minified_colon_spacing: bool,
+    crlf: bool,
+    trailing_newline: bool,
+    space_in_empty: bool,
+}
+
+impl Default for FormatStyle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FormatStyle {
+    /// Create the default style.
+    pub fn new() -> Self {
+        Self {
+            space_before_colon: false,
+            space_after_colon: true,
+            minified_colon_spacing: false,
+            crlf: false,
+            trailing_newline: false,
+            space_in_empty: false,
+        }
+    }
+
+    /// Put a space before `:` in objects like `"key" : value`. The default value is `false`.
+    pub fn space_before_colon(mut self, enabled: bool) -> Self {
+        self.space_before_colon = enabled;
+        self
+    }
+
+    /// Put a space after `:` in objects like `"key": value`. The default value is `true`.
+    pub fn space_after_colon(mut self, enabled: bool) -> Self {
+        self.space_after_colon = enabled;
+        self
+    }
+
+    /// Apply the spaces around `:` to the output without indentation as well. By default, the output without
+    /// indentation has no space around `:`.
+    pub fn minified_colon_spacing(mut self, enabled: bool) -> Self {
+        self.minified_colon_spacing = enabled;
+        self
+    }
+
+    /// Use CRLF instead of LF for line endings. The default value is `false`.
+    pub fn crlf(mut self, enabled: bool) -> Self {
+        self.crlf = enabled;
+        self
+    }
+
+    /// Put a newline at the end of the output. The default value is `false`.
+    pub fn trailing_newline(mut self, enabled: bool) -> Self {
+        self.trailing_newline = enabled;
+        self
+    }
+
+    /// Write empty array and object as `[ ]` and `{ }` instead of `[]` and `{}` when indentation is enabled. The
+    /// default value is `false`.
+    pub fn space_in_empty(mut self, enabled: bool) -> Self {
+        self.space_in_empty = enabled;
+        self
+    }
+
+    fn colon(&self, pretty: bool) -> &'static str {
+        if !pretty && !self.minified_colon_spacing {
+            return ":";
+        }
+        match (self.space_before_colon, self.space_after_colon) {
+            (false, false) => ":",
+            (false, true) => ": ",
+            (true, false) => " :",
+            (true, true) => " : ",
+        }
+    }
+
+    fn newline(&self) -> &'static str {
+        if self.crlf {
+            "\r\n"
+        } else {
+            "\n"
+        }
+    }
+}
+
+/// Palette of colors for the syntax-colored output of [`JsonGenerator`]. Set it via [`JsonGenerator::color`].
+///
+/// Each color is a parameter of ANSI SGR escape sequence such as `"32"` (green) or `"34;1"` (bold blue). The value is
+/// written between `\x1b[` and `m` before the token and `\x1b[0m` is written after the token. An empty string means
+/// the token is not colored. Whitespaces such as indentation and newlines are never colored so the output is exactly
+/// the same as the output without colors once the escape sequences are removed.
+///
+/// ```
+/// use tinyjson::{ColorPalette, JsonGenerator, JsonValue};
+///
+/// let v: JsonValue = r#"{"a": [1, null]}"#.parse().unwrap();
+/// let palette = ColorPalette::new().key("33").punctuation("");
+/// let mut buf = vec![];
+/// JsonGenerator::new(&mut buf).color(palette).generate(&v).unwrap();
+/// assert_eq!(
+///     String::from_utf8(buf).unwrap(),
+///     "{\x1b[33m\"a\"\x1b[0m:[\x1b[36m1\x1b[0m,\x1b[90mnull\x1b[0m]}",
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorPalette<'a> {
+    key: &'a str,
+    string: &'a str,
+    number: &'a str,
+    boolean: &'a str,
+    null: &'a str,
+    punctuation: &'a str,
+}
+
+impl Default for ColorPalette<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> ColorPalette<'a> {
+    /// Create the default palette. Keys are bold blue, strings are green, numbers are cyan, booleans are yellow, null
+    /// is gray and punctuations such as `[`, `:` and `,` are bold.
+    pub fn new() -> Self {
+        Self {
+            key: "34;1",
+            string: "32",
+            number: "36",
+            boolean: "33",
+            null: "90",
+            punctuation: "1",
+        }
+    }
+
+    /// Create the palette which colors nothing. Set colors of some tokens to it to color only the tokens.
+    pub fn plain() -> Self {
+        Self {
+            key: "",
+            string: "",
+            number: "",
+            boolean: "",
+            null: "",
+            punctuation: "",
+        }
+    }
+
+    /// Set the color of object keys.
+    pub fn key(mut self, color: &'a str) -> Self {
+        self.key = color;
+        self
+    }
+
+    /// Set the color of string values.
+    pub fn string(mut self, color: &'a str) -> Self {
+        self.string = color;
+        self
+    }
+
+    /// Set the color of numbers.
+    pub fn number(mut self, color: &'a str) -> Self {
+        self.number = color;
+        self
+    }
+
+    /// Set the color of `true` and `false`.
+    pub fn boolean(mut self, color: &'a str) -> Self {
+        self.boolean = color;
+        self
+    }
+
+    /// Set the color of `null`.
+    pub fn null(mut self, color: &'a str) -> Self {
+        self.null = color;
+        self
+    }
+
+    /// Set the color of `[`, `]`, `{`, `}`, `:` and `,`.
+    pub fn punctuation(mut self, color: &'a str) -> Self {
+        self.punctuation = color;
+        self
+    }
+}
+
+/// Policy to write infinity and NaN which cannot be represented in JSON. Set it via [`JsonGenerator::non_finite`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NonFinitePolicy {
+    /// Fail to generate the output. This is the default policy.
+    #[default]
+    Error,
+    /// Write `null` instead.
+    WriteNull,
+    /// Write `"NaN"`, `"Infinity"` or `"-Infinity"` string.
+    WriteAsString,
+    /// Write `NaN`, `Infinity` or `-Infinity` literal as JSON5 and JavaScript. Note that the output is no longer valid
+    /// JSON. [`crate::JsonParser::allow_non_finite`] can read it back.
+    WriteLiteral,
+}
+
+// Buffer on stack to format a number without allocation
+struct NumberBuf {
+    buf: [u8; 32],
+    len: usize,
+}
+
+impl NumberBuf {
+    fn new() -> Self {
+        Self {
+            buf: [0; 32],
+            len: 0,
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        str::from_utf8(&self.buf[..self.len]).unwrap()
+    }
+}
+
+impl fmt::Write for NumberBuf {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        if end > self.buf.len() {
+            return Err(fmt::Error);
+        }
+        self.buf[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+type KeyCompare<'a> = Box<dyn Fn(&str, &str) -> Ordering + 'a>;
+
+// Order of keys of objects in the output
+enum KeyOrder<'a> {
+    Unordered,
+    Sorted,
+    Utf16,
+    Priority(&'a [&'a str]),
+    Custom(KeyCompare<'a>),
+}
+
+impl KeyOrder<'_> {
+    fn compare(&self, l: &str, r: &str) -> Ordering {
+        match self {
+            KeyOrder::Unordered | KeyOrder::Sorted => l.cmp(r),
+            KeyOrder::Utf16 => l.encode_utf16().cmp(r.encode_utf16()),
+            KeyOrder::Priority(keys) => {
+                let pos = |k| keys.iter().position(|p| *p == k).unwrap_or(keys.len());
+                pos(l).cmp(&pos(r)).then_with(|| l.cmp(r))
+            }
+            KeyOrder::Custom(cmp) => cmp(l, r),
+        }
+    }
+}
+
+// Decompose the positive finite number `f` into `m * 2^e` where `m` is odd
+fn decompose(f: f64) -> (u64, i32) {
+    let bits = f.to_bits();
+    let biased_exp = ((bits >> 52) & 0x7ff) as i32;
+    let fraction = bits & ((1 << 52) - 1);
+    let (m, e) = if biased_exp == 0 {
+        (fraction, -1074)
+    } else {
+        (fraction | (1 << 52), biased_exp - 1075)
+    };
+    (m >> m.trailing_zeros(), e + m.trailing_zeros() as i32)
+}
+
+// When the positive finite number `f` is exactly in the middle of two decimals with `p` digits after the decimal
+// point, `{:.*}` rounds half to even but ECMAScript's `Number.prototype.toFixed` rounds half away from zero. Returns
+// the larger decimal in the case. For example, 0.125 must be written as 0.13 instead of 0.12 with 2 digits.
+fn round_half_up(f: f64, p: usize) -> Option<String> {
+    // `f * 10^p == m * 2^(e+p) * 5^p` is exactly in the middle of two integers only when `e + p == -1`
+    let (_, e) = decompose(f);
+    if e as i64 != -(p as i64) - 1 {
+        return None;
+    }
+
+    // The exact value has `p + 1` digits after the decimal point and the last digit is 5. Drop it and round up
+    let mut digits = format!("{:.*}", p + 1, f).into_bytes();
+    digits.pop();
+    if digits.last() == Some(&b'.') {
+        digits.pop();
+    }
+    let mut carry = true;
+    for d in digits.iter_mut().rev() {
+        match *d {
+            b'.' => {}
+            b'9' => *d = b'0',
+            _ => {
+                *d += 1;
+                carry = false;
+                break;
+            }
+        }
+    }
+    if carry {
+        digits.insert(0, b'1');
+    }
+    String::from_utf8(digits).ok()
+}
+
+// When the number is exactly in the middle of two shortest decimals, `{:e}` chooses the larger one but ECMAScript
+// requires to choose the even one. Returns the even digits in the case. For example, 1424953923781206.25 must be
+// written as 1424953923781206.2 instead of 1424953923781206.3. The value is `0.{digits} * 10^n` where `digits` is
+// `int` followed by `frac`.
+fn round_half_to_even(f: f64, int: &str, frac: &str, n: i32) -> Option<NumberBuf> {
+    let last = frac.bytes().last().unwrap_or(int.as_bytes()[0]);
+    if last & 1 == 0 {
+        return None;
+    }
+
+    let (m, e) = decompose(f);
+
+    // The middle point is `d * 10^q` where `d` is odd since its last digit is 5. Comparing the factors of 2,
+    // `d * 10^q == m * 2^e` can be true only when `q == e`.
+    let q = n - (int.len() + frac.len()) as i32 - 1;
+    if q != e {
+        return None;
+    }
+
+    let s = int.bytes().chain(frac.bytes()).try_fold(0u64, |acc, b| {
+        acc.checked_mul(10)?.checked_add(u64::from(b - b'0'))
+    })?;
+    let pow5 = 5u64.checked_pow(q.unsigned_abs())?;
+    for (mid, even) in [(s * 10 - 5, s - 1), (s * 10 + 5, s + 1)] {
+        let exact = if q >= 0 {
+            mid.checked_mul(pow5) == Some(m)
+        } else {
+            m.checked_mul(pow5) == Some(mid)
+        };
+        // Skip the candidate whose number of digits changes (e.g. 19 -> 20)
+        if !exact || even % 10 == 0 {
+            continue;
+        }
+
+        let mut buf = NumberBuf::new();
+        write!(buf, "{}e{}", even, q + 1).unwrap();
+        if buf.as_str().parse::<f64>() != Ok(f) {
+            return None;
+        }
+        let mut buf = NumberBuf::new();
+        write!(buf, "{}", even).unwrap();
+        return Some(buf);
+    }
+    None
+}
+
+// Output with an internal buffer to reduce the number of small writes to the underlying output. The buffer is
+// allocated lazily on the first write. When the size is zero, all writes go to the underlying output directly.
+//
+// Errors of the underlying output are kept in `error` and a `JsonGenerateError` of `Io` kind is returned instead so
+// that the generator can handle all errors in the same type. The original error is taken back by `Buffered::error`.
+struct Buffered<W: JsonWrite> {
+    out: W,
+    buf: String,
+    size: usize,
+    error: Option<W::Error>,
+}
+
+impl<W: JsonWrite> Buffered<W> {
+    fn new(out: W, size: usize) -> Self {
+        Self {
+            out,
+            buf: String::new(),
+            size,
+            error: None,
+        }
+    }
+
+    #[inline]
+    fn write_out(&mut self, s: &str) -> Result<(), JsonGenerateError> {
+        match self.out.write_str(s) {
+            Ok(()) => Ok(()),
+            Err(err) => Err(self.output_error(err)),
+        }
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn output_error(&mut self, err: W::Error) -> JsonGenerateError {
+        self.error = Some(err);
+        // Path is not recorded since this error is replaced with the original error
+        let mut err =
+            JsonGenerateError::new(JsonGenerateErrorKind::Io, "Writing the output failed");
+        err.0.path = None;
+        err
+    }
+
+    #[inline]
+    fn write_str(&mut self, s: &str) -> Result<(), JsonGenerateError> {
+        if self.size == 0 {
+            return self.write_out(s);
+        }
+        self.write_buffered(s)
+    }
+
+    // Separated from `write_str` to keep the unbuffered path small enough to be inlined
+    #[inline(never)]
+    fn write_buffered(&mut self, s: &str) -> Result<(), JsonGenerateError> {
+        if self.buf.len() + s.len() > self.size {
+            self.flush()?;
+            if s.len() >= self.size {
+                return self.write_out(s);
+            }
+        }
+        if self.buf.capacity() == 0 {
+            self.buf.reserve(self.size);
+        }
+        self.buf.push_str(s);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), JsonGenerateError> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        let buf = core::mem::take(&mut self.buf);
+        let ret = self.write_out(&buf);
+        self.buf = buf;
+        self.buf.clear();
+        ret
+    }
+
+    // Convert the error returned from the generator into the error type of the output
+    fn error(&mut self, err: JsonGenerateError) -> W::Error {
+        match self.error.take() {
+            Some(e) if err.0.kind == JsonGenerateErrorKind::Io => e,
+            _ => W::invalid_value(err),
+        }
+    }
+}
+
+// Output which only counts the number of bytes of UTF-8 byte sequence
+pub(crate) struct ByteCount(pub(crate) usize);
+
+impl JsonWrite for ByteCount {
+    type Error = JsonGenerateError;
+
+    fn write_str(&mut self, s: &str) -> Result<(), JsonGenerateError> {
+        self.0 += s.len();
+        Ok(())
+    }
+
+    fn invalid_value(err: JsonGenerateError) -> JsonGenerateError {
+        err
+    }
+}
+
+// Output which only counts the number of characters. Writing fails when the count exceeds the limit
+struct Measure {
+    len: usize,
+    limit: usize,
+}
+
+impl JsonWrite for Measure {
+    type Error = ();
+
+    fn write_str(&mut self, s: &str) -> Result<(), ()> {
+        self.len += s.chars().count();
+        if self.len > self.limit {
+            Err(())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn invalid_value(_err: JsonGenerateError) {}
+}
+
 /// JSON serializer for `JsonValue`.
 ///
 /// Basically you don't need to use this struct directly since `JsonValue::stringify` or `JsonValue::format` methods are
@@ -42,40 +755,557 @@ pub type JsonGenerateResult = Result<String, JsonGenerateError>;
 ///
 /// assert_eq!(String::from_utf8(buf).unwrap(), "\"hello, world\"");
 /// ```
-pub struct JsonGenerator<'indent, W: Write> {
-    out: W,
-    indent: Option<&'indent str>,
+pub struct JsonGenerator<'a, W: JsonWrite> {
+    out: Buffered<W>,
+    indent: Option<&'a str>,
+    precision: Option<usize>,
+    whole_numbers_as_integers: bool,
+    ascii_only: bool,
+    html_safe: bool,
+    non_finite: NonFinitePolicy,
+    style: FormatStyle,
+    key_order: KeyOrder<'a>,
+    max_width: Option<usize>,
+    color: Option<ColorPalette<'a>>,
+    redaction: Option<(&'a Redaction<'a>, RedactionState)>,
+    json5: bool,
+    single_quotes: bool,
+    inline: bool, // Writing an array or object in one line with `max_width`
 }
 
-impl<'indent, W: Write> JsonGenerator<'indent, W> {
-    /// Create a new `JsonGenerator` object. The serialized byte sequence will be written to the given `io::Write`
-    /// object.
-    pub fn new(out: W) -> Self {
-        Self { out, indent: None }
+impl<'a, W: JsonWrite> JsonGenerator<'a, W> {
+    /// Create a new `JsonGenerator` object. The serialized byte sequence will be written to the given `io::Write`
+    /// object. To write the serialized string to a `fmt::Write` object, wrap it with [`FmtWriter`].
+    pub fn new(out: W) -> Self {
+        Self {
+            out: Buffered::new(out, 0),
+            indent: None,
+            precision: None,
+            whole_numbers_as_integers: false,
+            ascii_only: false,
+            html_safe: false,
+            non_finite: NonFinitePolicy::Error,
+            style: FormatStyle::new(),
+            key_order: KeyOrder::Unordered,
+            max_width: None,
+            color: None,
+            redaction: None,
+            json5: false,
+            single_quotes: false,
+            inline: false,
+        }
+    }
+
+    /// Set indent string. This will be used by [`JsonGenerator::generate`].
+    /// ```
+    /// use tinyjson::{JsonGenerator, JsonValue};
+    ///
+    /// let v = JsonValue::from(vec![1.0.into(), 2.0.into(), 3.0.into()]);
+    /// let mut buf = vec![];
+    /// let mut gen = JsonGenerator::new(&mut buf).indent("        ");
+    /// gen.generate(&v).unwrap();
+    ///
+    /// assert_eq!(String::from_utf8(buf).unwrap(),
+    /// "[
+    ///         1,
+    ///         2,
+    ///         3
+    /// ]");
+    /// ```
+    pub fn indent(mut self, indent: &'a str) -> Self {
+        self.indent = Some(indent);
+        self
+    }
+
+    /// Set the maximum width of lines. This is effective only when the indentation is set by
+    /// [`JsonGenerator::indent`]. An array or an object is written in one line when it fits in the remaining width of
+    /// the line. Otherwise its elements are written in separate lines as usual. The width is counted in characters.
+    ///
+    /// ```
+    /// use tinyjson::{JsonGenerator, JsonValue};
+    ///
+    /// let v: JsonValue = r#"{"type": "LineString", "coordinates": [[139.69, 35.68], [135.50, 34.69]]}"#
+    ///     .parse()
+    ///     .unwrap();
+    /// let mut buf = vec![];
+    /// JsonGenerator::new(&mut buf)
+    ///     .indent("  ")
+    ///     .max_width(40)
+    ///     .sort_keys(true)
+    ///     .generate(&v)
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(buf).unwrap(),
+    /// r#"{
+    ///   "coordinates": [
+    ///     [139.69, 35.68],
+    ///     [135.5, 34.69]
+    ///   ],
+    ///   "type": "LineString"
+    /// }"#,
+    /// );
+    /// ```
+    pub fn max_width(mut self, width: usize) -> Self {
+        self.max_width = Some(width);
+        self
+    }
+
+    /// Set the style of the output such as spaces around `:` and line endings. See [`FormatStyle`] for more details.
+    ///
+    /// ```
+    /// use tinyjson::{FormatStyle, JsonGenerator, JsonValue};
+    ///
+    /// let v: JsonValue = r#"{"a": [1, 2]}"#.parse().unwrap();
+    /// let mut buf = vec![];
+    /// JsonGenerator::new(&mut buf)
+    ///     .indent("  ")
+    ///     .style(FormatStyle::new().space_before_colon(true).crlf(true))
+    ///     .generate(&v)
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(buf).unwrap(),
+    ///     "{\r\n  \"a\" : [\r\n    1,\r\n    2\r\n  ]\r\n}",
+    /// );
+    /// ```
+    pub fn style(mut self, style: FormatStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Set the size of the internal buffer in bytes. The output is batched in the buffer to avoid many small writes
+    /// to the underlying output such as unbuffered `File` or `TcpStream`. The buffer is flushed when it is full and at
+    /// the end of [`JsonGenerator::generate`]. The default size is zero, which disables the buffering since it only
+    /// adds copies when the output is already buffered or in memory like `Vec<u8>`.
+    ///
+    /// When the generator is used by [`crate::JsonWriter`], the buffered output is written when the buffer is full,
+    /// by [`crate::JsonWriter::flush`] and by [`crate::JsonWriter::finish`].
+    ///
+    /// ```
+    /// use tinyjson::{JsonGenerator, JsonValue};
+    /// use std::fs::File;
+    ///
+    /// let v = JsonValue::from(vec![1.0.into(), 2.0.into()]);
+    /// let path = std::env::temp_dir().join("tinyjson_buffer_size.json");
+    /// // `File` is not buffered
+    /// let mut file = File::create(&path).unwrap();
+    /// JsonGenerator::new(&mut file).buffer_size(8 * 1024).generate(&v).unwrap();
+    /// assert_eq!(std::fs::read(&path).unwrap(), b"[1,2]");
+    /// ```
+    pub fn buffer_size(mut self, size: usize) -> Self {
+        self.out.size = size;
+        self
+    }
+
+    /// Color the output with ANSI escape sequences for terminals. Keys, strings, numbers, booleans, null and
+    /// punctuations are colored with the colors in the given palette. See [`ColorPalette`] for more details. This works
+    /// for both the output with and without indentation.
+    ///
+    /// ```
+    /// use tinyjson::{ColorPalette, JsonGenerator, JsonValue};
+    ///
+    /// let v = JsonValue::from(vec![true.into()]);
+    /// let mut buf = vec![];
+    /// JsonGenerator::new(&mut buf).indent("  ").color(ColorPalette::new()).generate(&v).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(buf).unwrap(),
+    ///     "\x1b[1m[\x1b[0m\n  \x1b[33mtrue\x1b[0m\n\x1b[1m]\x1b[0m",
+    /// );
+    /// ```
+    pub fn color(mut self, palette: ColorPalette<'a>) -> Self {
+        self.color = Some(palette);
+        self
+    }
+
+    /// Write the output in [JSON5](https://json5.org/) format for configuration files edited by humans. In JSON5
+    /// mode,
+    ///
+    /// - Object keys are not quoted when they are valid ECMAScript identifiers consisting of ASCII characters
+    /// - Trailing commas are put after the last elements of arrays and objects written in multiple lines
+    /// - Infinity and NaN are written as `Infinity`, `-Infinity` and `NaN` literals unless other policy than
+    ///   [`NonFinitePolicy::Error`] is set via [`JsonGenerator::non_finite`]
+    ///
+    /// Strings can be quoted with single quotes via [`JsonGenerator::single_quotes`].
+    ///
+    /// ```
+    /// use tinyjson::{JsonGenerator, JsonValue};
+    ///
+    /// let mut v: JsonValue = r#"{"name": "foo", "max-size": [1, 2]}"#.parse().unwrap();
+    /// v["max-size"][1] = f64::INFINITY.into();
+    /// let mut buf = vec![];
+    /// JsonGenerator::new(&mut buf).indent("  ").sort_keys(true).json5(true).generate(&v).unwrap();
+    /// assert_eq!(String::from_utf8(buf).unwrap(),
+    /// r#"{
+    ///   "max-size": [
+    ///     1,
+    ///     Infinity,
+    ///   ],
+    ///   name: "foo",
+    /// }"#);
+    /// ```
+    pub fn json5(mut self, enabled: bool) -> Self {
+        self.json5 = enabled;
+        self
+    }
+
+    /// Quote strings with single quotes instead of double quotes in JSON5 mode. `'` in strings is escaped as `\'` and
+    /// `"` is not escaped. This option has no effect unless JSON5 mode is enabled via [`JsonGenerator::json5`].
+    ///
+    /// ```
+    /// use tinyjson::{JsonGenerator, JsonValue};
+    ///
+    /// let v: JsonValue = r#"{"it's": "\"quoted\""}"#.parse().unwrap();
+    /// let mut buf = vec![];
+    /// JsonGenerator::new(&mut buf).json5(true).single_quotes(true).generate(&v).unwrap();
+    /// assert_eq!(String::from_utf8(buf).unwrap(), r#"{'it\'s':'"quoted"'}"#);
+    /// ```
+    pub fn single_quotes(mut self, enabled: bool) -> Self {
+        self.single_quotes = enabled;
+        self
+    }
+
+    /// Replace values of sensitive keys such as passwords and tokens with a placeholder string. Rules to match the keys
+    /// are given by [`Redaction`]. The value is not modified nor cloned. This option is not reset by
+    /// [`JsonGenerator::canonical`] since it affects the content rather than the format of the output.
+    ///
+    /// When [`crate::JsonWriter`] is used, the keys and the indices of the values are tracked and the rules are applied
+    /// in the same way. A redacted array or object started by [`crate::JsonWriter::begin_array`] or
+    /// [`crate::JsonWriter::begin_object`] is written as the placeholder and its content is not written.
+    ///
+    /// ```
+    /// use tinyjson::{JsonGenerator, JsonValue, Redaction};
+    ///
+    /// let v: JsonValue = r#"[{"user": "alice", "password": "secret"}]"#.parse().unwrap();
+    /// let redaction = Redaction::new(&["password"]);
+    /// let mut buf = vec![];
+    /// JsonGenerator::new(&mut buf)
+    ///     .indent("  ")
+    ///     .sort_keys(true)
+    ///     .redact(&redaction)
+    ///     .generate(&v)
+    ///     .unwrap();
+    /// assert_eq!(String::from_utf8(buf).unwrap(),
+    /// r#"[
+    ///   {
+    ///     "password": "[REDACTED]",
+    ///     "user": "alice"
+    ///   }
+    /// ]"#);
+    /// ```
+    pub fn redact(mut self, redaction: &'a Redaction<'a>) -> Self {
+        self.redaction = Some((redaction, redaction.root()));
+        self
+    }
+
+    /// Write numbers with the fixed number of digits after the decimal point like `Number.prototype.toFixed` in
+    /// JavaScript. A number exactly in the middle of two candidates is rounded half away from zero. By default, numbers
+    /// are written in the shortest form which can be parsed back to the same value. See [`JsonGenerator::generate`] for
+    /// more details.
+    ///
+    /// ```
+    /// use tinyjson::{JsonGenerator, JsonValue};
+    ///
+    /// let v = JsonValue::from(vec![1.0.into(), 0.125.into(), (-2.0 / 3.0).into()]);
+    /// let mut buf = vec![];
+    /// JsonGenerator::new(&mut buf).fixed_precision(2).generate(&v).unwrap();
+    /// assert_eq!(String::from_utf8(buf).unwrap(), "[1.00,0.13,-0.67]");
+    /// ```
+    pub fn fixed_precision(mut self, digits: usize) -> Self {
+        self.precision = Some(digits);
+        self
+    }
+
+    /// Always write whole numbers as integers without fraction part and exponent part. This takes precedence over
+    /// [`JsonGenerator::fixed_precision`]. Note that very large numbers such as `1e300` are written with all digits.
+    ///
+    /// ```
+    /// use tinyjson::{JsonGenerator, JsonValue};
+    ///
+    /// let v = JsonValue::from(vec![1e21.into(), 0.5.into()]);
+    ///
+    /// let mut buf = vec![];
+    /// JsonGenerator::new(&mut buf).generate(&v).unwrap();
+    /// assert_eq!(String::from_utf8(buf).unwrap(), "[1e+21,0.5]");
+    ///
+    /// let mut buf = vec![];
+    /// JsonGenerator::new(&mut buf).whole_numbers_as_integers(true).generate(&v).unwrap();
+    /// assert_eq!(String::from_utf8(buf).unwrap(), "[1000000000000000000000,0.5]");
+    /// ```
+    pub fn whole_numbers_as_integers(mut self, enabled: bool) -> Self {
+        self.whole_numbers_as_integers = enabled;
+        self
+    }
+
+    /// Set the policy to write infinity and NaN. By default, generating the output fails when they are included in the
+    /// value since JSON cannot represent them.
+    ///
+    /// ```
+    /// use tinyjson::{JsonGenerator, JsonValue, NonFinitePolicy};
+    ///
+    /// let v = JsonValue::from(vec![f64::NAN.into(), f64::NEG_INFINITY.into()]);
+    ///
+    /// let mut buf = vec![];
+    /// assert!(JsonGenerator::new(&mut buf).generate(&v).is_err());
+    ///
+    /// let mut buf = vec![];
+    /// JsonGenerator::new(&mut buf)
+    ///     .non_finite(NonFinitePolicy::WriteNull)
+    ///     .generate(&v)
+    ///     .unwrap();
+    /// assert_eq!(String::from_utf8(buf).unwrap(), "[null,null]");
+    ///
+    /// let mut buf = vec![];
+    /// JsonGenerator::new(&mut buf)
+    ///     .non_finite(NonFinitePolicy::WriteAsString)
+    ///     .generate(&v)
+    ///     .unwrap();
+    /// assert_eq!(String::from_utf8(buf).unwrap(), r#"["NaN","-Infinity"]"#);
+    /// ```
+    pub fn non_finite(mut self, policy: NonFinitePolicy) -> Self {
+        self.non_finite = policy;
+        self
+    }
+
+    /// Escape all non-ASCII characters in strings with `\uXXXX` sequences so that the output consists of only 7-bit
+    /// ASCII characters. Characters outside the Basic Multilingual Plane are escaped as UTF-16 surrogate pairs. The
+    /// output is parsed back to the same value.
+    ///
+    /// ```
+    /// use tinyjson::{JsonGenerator, JsonValue};
+    ///
+    /// let v = JsonValue::from("caf\u{e9} \u{1f600}".to_string());
+    /// let mut buf = vec![];
+    /// JsonGenerator::new(&mut buf).ascii_only(true).generate(&v).unwrap();
+    /// assert_eq!(String::from_utf8(buf).unwrap(), r#""caf\u00e9 \ud83d\ude00""#);
+    /// ```
+    pub fn ascii_only(mut self, enabled: bool) -> Self {
+        self.ascii_only = enabled;
+        self
+    }
+
+    /// Escape `<`, `>`, `&`, `'`, U+2028 and U+2029 in strings with `\uXXXX` sequences so that the output can be
+    /// safely embedded in HTML `<script>` elements. `</script>` in strings can no longer close the element and the
+    /// output is also valid as JavaScript source since U+2028 and U+2029 are line terminators in old JavaScript. This
+    /// is the same escaping as `json.HTMLEscape` in Go.
+    ///
+    /// ```
+    /// use tinyjson::{JsonGenerator, JsonValue};
+    ///
+    /// let v = JsonValue::from("</script><script>alert('&')</script>".to_string());
+    /// let mut buf = vec![];
+    /// JsonGenerator::new(&mut buf).html_safe(true).generate(&v).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(buf).unwrap(),
+    ///     r#""\u003c/script\u003e\u003cscript\u003ealert(\u0027\u0026\u0027)\u003c/script\u003e""#,
+    /// );
+    /// ```
+    pub fn html_safe(mut self, enabled: bool) -> Self {
+        self.html_safe = enabled;
+        self
     }
 
-    /// Set indent string. This will be used by [`JsonGenerator::generate`].
+    /// Write keys of objects in lexicographical order. Since objects are `BTreeMap` values, this is the same as the
+    /// default order which is the iteration order of the map. This option resets the order set by
+    /// [`JsonGenerator::key_priority`] and other options.
+    ///
     /// ```
     /// use tinyjson::{JsonGenerator, JsonValue};
     ///
-    /// let v = JsonValue::from(vec![1.0.into(), 2.0.into(), 3.0.into()]);
+    /// let v: JsonValue = r#"{"b": 1, "c": 2, "a": 3}"#.parse().unwrap();
     /// let mut buf = vec![];
-    /// let mut gen = JsonGenerator::new(&mut buf).indent("        ");
-    /// gen.generate(&v).unwrap();
+    /// JsonGenerator::new(&mut buf).sort_keys(true).generate(&v).unwrap();
+    /// assert_eq!(String::from_utf8(buf).unwrap(), r#"{"a":3,"b":1,"c":2}"#);
+    /// ```
+    pub fn sort_keys(mut self, enabled: bool) -> Self {
+        self.key_order = if enabled {
+            KeyOrder::Sorted
+        } else {
+            KeyOrder::Unordered
+        };
+        self
+    }
+
+    /// Write the given keys first in the order of the slice. Other keys follow them in lexicographical order. This is
+    /// useful to write well-known keys first like `"name"` and `"version"` in `package.json`.
     ///
-    /// assert_eq!(String::from_utf8(buf).unwrap(),
-    /// "[
-    ///         1,
-    ///         2,
-    ///         3
-    /// ]");
     /// ```
-    pub fn indent(mut self, indent: &'indent str) -> Self {
-        self.indent = Some(indent);
+    /// use tinyjson::{JsonGenerator, JsonValue};
+    ///
+    /// let v: JsonValue = r#"{"main": "index.js", "version": "1.0.0", "license": "MIT", "name": "foo"}"#
+    ///     .parse()
+    ///     .unwrap();
+    /// let mut buf = vec![];
+    /// JsonGenerator::new(&mut buf)
+    ///     .key_priority(&["name", "version"])
+    ///     .generate(&v)
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(buf).unwrap(),
+    ///     r#"{"name":"foo","version":"1.0.0","license":"MIT","main":"index.js"}"#,
+    /// );
+    /// ```
+    pub fn key_priority(mut self, keys: &'a [&'a str]) -> Self {
+        self.key_order = KeyOrder::Priority(keys);
+        self
+    }
+
+    /// Write keys of objects in the order decided by the given comparator function.
+    ///
+    /// ```
+    /// use tinyjson::{JsonGenerator, JsonValue};
+    ///
+    /// let v: JsonValue = r#"{"bb": 1, "a": 2, "ccc": 3}"#.parse().unwrap();
+    /// let mut buf = vec![];
+    /// JsonGenerator::new(&mut buf)
+    ///     .sort_keys_by(|l, r| r.len().cmp(&l.len()))
+    ///     .generate(&v)
+    ///     .unwrap();
+    /// assert_eq!(String::from_utf8(buf).unwrap(), r#"{"ccc":3,"bb":1,"a":2}"#);
+    /// ```
+    pub fn sort_keys_by<F>(mut self, compare: F) -> Self
+    where
+        F: Fn(&str, &str) -> Ordering + 'a,
+    {
+        self.key_order = KeyOrder::Custom(Box::new(compare));
+        self
+    }
+
+    /// Configure the generator to write the canonical form defined by
+    /// [RFC 8785 JSON Canonicalization Scheme (JCS)](https://www.rfc-editor.org/rfc/rfc8785). Keys of objects are
+    /// sorted by UTF-16 code units, numbers are written in the same format as JavaScript, no whitespace is inserted,
+    /// and only the minimal escaping is used for strings. This resets other options affecting the output, so call this
+    /// method last. Infinity and NaN are rejected as error. See also [`canonicalize`].
+    ///
+    /// ```
+    /// use tinyjson::{JsonGenerator, JsonValue};
+    ///
+    /// let v: JsonValue = r#"{"b": [1.0, 1e21], "a": "\u00e9"}"#.parse().unwrap();
+    /// let mut buf = vec![];
+    /// JsonGenerator::new(&mut buf).indent("  ").canonical().generate(&v).unwrap();
+    /// assert_eq!(String::from_utf8(buf).unwrap(), r#"{"a":"é","b":[1,1e+21]}"#);
+    /// ```
+    pub fn canonical(mut self) -> Self {
+        self.indent = None;
+        self.precision = None;
+        self.whole_numbers_as_integers = false;
+        self.ascii_only = false;
+        self.html_safe = false;
+        self.non_finite = NonFinitePolicy::Error;
+        self.style = FormatStyle::new();
+        self.key_order = KeyOrder::Utf16;
+        self.max_width = None;
+        self.color = None;
+        self.json5 = false;
+        self.single_quotes = false;
         self
     }
 
-    fn encode_string(&mut self, s: &str) -> io::Result<()> {
+    // Returns the entries of the object in sorted order. `None` means the entries should be written as they are.
+    fn sorted_entries<'m>(
+        &self,
+        m: &'m BTreeMap<String, JsonValue>,
+    ) -> Option<Vec<(&'m String, &'m JsonValue)>> {
+        // `BTreeMap` iterates the entries in lexicographical order
+        if let KeyOrder::Unordered | KeyOrder::Sorted = self.key_order {
+            return None;
+        }
+        let mut entries: Vec<_> = m.iter().collect();
+        entries.sort_by(|(l, _), (r, _)| self.key_order.compare(l, r));
+        Some(entries)
+    }
+
+    fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> Result<(), JsonGenerateError> {
+        let mut adapter = FmtAdapter {
+            out: &mut self.out,
+            error: None,
+        };
+        // `fmt::Error` is returned only when writing to the output failed
+        fmt::write(&mut adapter, args).map_err(|_| adapter.error.unwrap())
+    }
+
+    // Write the output of `f` surrounded by the escape sequences of the color picked from the palette
+    fn colored<F>(
+        &mut self,
+        pick: fn(&ColorPalette<'a>) -> &'a str,
+        f: F,
+    ) -> Result<(), JsonGenerateError>
+    where
+        F: FnOnce(&mut Self) -> Result<(), JsonGenerateError>,
+    {
+        let color = match &self.color {
+            Some(palette) => pick(palette),
+            None => "",
+        };
+        if color.is_empty() {
+            return f(self);
+        }
+        write!(self, "\x1b[{}m", color)?;
+        f(self)?;
+        self.out.write_str("\x1b[0m")
+    }
+
+    // Write the child value at the segment with `f`, or write the placeholder instead when the value is redacted
+    fn child<F>(&mut self, seg: Segment<'_>, f: F) -> Result<(), JsonGenerateError>
+    where
+        F: FnOnce(&mut Self) -> Result<(), JsonGenerateError>,
+    {
+        match self.enter_child(seg) {
+            (true, _) => self.write_placeholder(),
+            (false, parent) => {
+                let ret = f(self);
+                self.leave_child(parent);
+                ret
+            }
+        }
+    }
+
+    // Move to the child value at the segment. Returns whether the child value is redacted and the state of the parent
+    // value to be restored by `leave_child` after writing the child value
+    pub(crate) fn enter_child(&mut self, seg: Segment<'_>) -> (bool, Option<RedactionState>) {
+        let (redaction, state) = match &mut self.redaction {
+            Some((r, s)) => (*r, s),
+            None => return (false, None),
+        };
+        let (child, redacted) = redaction.child(state, seg);
+        if redacted {
+            return (true, None);
+        }
+        (false, Some(mem::replace(state, child)))
+    }
+
+    pub(crate) fn leave_child(&mut self, parent: Option<RedactionState>) {
+        if let (Some((_, state)), Some(parent)) = (&mut self.redaction, parent) {
+            *state = parent;
+        }
+    }
+
+    pub(crate) fn redacting(&self) -> bool {
+        self.redaction.is_some()
+    }
+
+    pub(crate) fn write_placeholder(&mut self) -> Result<(), JsonGenerateError> {
+        let placeholder = match &self.redaction {
+            Some((r, _)) => r.placeholder_str(),
+            None => return Ok(()),
+        };
+        self.colored(|p| p.string, |gen| gen.encode_string(placeholder))
+    }
+
+    #[inline]
+    fn write_punct(&mut self, s: &str) -> Result<(), JsonGenerateError> {
+        if self.color.is_none() {
+            return self.out.write_str(s);
+        }
+        self.colored(|p| p.punctuation, |gen| gen.out.write_str(s))
+    }
+
+    fn encode_key(&mut self, key: &str) -> Result<(), JsonGenerateError> {
+        if self.json5 && is_identifier(key) {
+            return self.colored(|p| p.key, |gen| gen.out.write_str(key));
+        }
+        self.colored(|p| p.key, |gen| gen.encode_string(key))
+    }
+
+    fn encode_string(&mut self, s: &str) -> Result<(), JsonGenerateError> {
         const B: u8 = b'b'; // \x08
         const T: u8 = b't'; // \x09
         const N: u8 = b'n'; // \x0a
@@ -83,176 +1313,518 @@ impl<'indent, W: Write> JsonGenerator<'indent, W> {
         const R: u8 = b'r'; // \x0d
         const Q: u8 = b'"'; // \x22
         const S: u8 = b'\\'; // \x5c
+        const A: u8 = b'\''; // \x27 in single-quoted string
         const U: u8 = 1; // non-printable
+        const H: u8 = 2; // escaped only when `html_safe` is enabled
+        const M: u8 = 3; // byte of non-ASCII character
 
         #[rustfmt::skip]
         const ESCAPE_TABLE: [u8; 256] = [
          // 0  1  2  3  4  5  6  7  8  9  A  B  C  D  E  F
             U, U, U, U, U, U, U, U, B, T, N, U, F, R, U, U, // 0
             U, U, U, U, U, U, U, U, U, U, U, U, U, U, U, U, // 1
-            0, 0, Q, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // 2
-            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // 3
+            0, 0, Q, 0, 0, 0, H, H, 0, 0, 0, 0, 0, 0, 0, 0, // 2
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, H, 0, H, 0, // 3
             0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // 4
             0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, S, 0, 0, 0, // 5
             0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // 6
             0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // 7
-            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // 8
-            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // 9
-            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // A
-            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // B
-            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // C
-            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // D
-            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // E
-            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // F
+            M, M, M, M, M, M, M, M, M, M, M, M, M, M, M, M, // 8
+            M, M, M, M, M, M, M, M, M, M, M, M, M, M, M, M, // 9
+            M, M, M, M, M, M, M, M, M, M, M, M, M, M, M, M, // A
+            M, M, M, M, M, M, M, M, M, M, M, M, M, M, M, M, // B
+            M, M, M, M, M, M, M, M, M, M, M, M, M, M, M, M, // C
+            M, M, M, M, M, M, M, M, M, M, M, M, M, M, M, M, // D
+            M, M, M, M, M, M, M, M, M, M, M, M, M, M, M, M, // E
+            M, M, M, M, M, M, M, M, M, M, M, M, M, M, M, M, // F
         ];
 
-        self.out.write_all(b"\"")?;
+        let single = self.json5 && self.single_quotes;
+        let quote = if single { "'" } else { "\"" };
+        self.out.write_str(quote)?;
+        // Scan bytes instead of characters since most bytes are written as-is. Options are checked only for the bytes
+        // which may be escaped
+        let (ascii_only, html_safe) = (self.ascii_only, self.html_safe);
+        let bytes = s.as_bytes();
         let mut start = 0;
-        for (i, c) in s.char_indices() {
-            let u = c as usize;
-            if u < 256 {
-                let esc = ESCAPE_TABLE[u];
-                if esc == 0 {
-                    continue;
-                }
-                if start != i {
-                    self.out.write_all(s[start..i].as_bytes())?;
-                }
-                if esc == U {
-                    write!(self.out, "\\u{:04x}", u)?;
-                } else {
-                    self.out.write_all(&[b'\\', esc])?;
+        let mut i = 0;
+        while let Some(pos) = bytes[i..]
+            .iter()
+            .position(|&b| ESCAPE_TABLE[b as usize] != 0)
+        {
+            i += pos;
+            let esc = match ESCAPE_TABLE[bytes[i] as usize] {
+                Q if single => 0,
+                H if bytes[i] == b'\'' && single && !html_safe => A,
+                H if !html_safe => 0,
+                M if !ascii_only && !html_safe => 0,
+                esc => esc,
+            };
+            if esc == 0 {
+                i += 1;
+                continue;
+            }
+
+            // Decode the character at `i`. `i` is always at a character boundary
+            let c = s[i..].chars().next().unwrap();
+            let len = c.len_utf8();
+            // Without `ascii_only`, only line terminators in JavaScript are escaped for `html_safe`
+            if esc == M && !ascii_only && c != '\u{2028}' && c != '\u{2029}' {
+                i += len;
+                continue;
+            }
+
+            if start != i {
+                self.out.write_str(&s[start..i])?;
+            }
+            if esc == M {
+                // Characters outside BMP are escaped as UTF-16 surrogate pair
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    write!(self, "\\u{:04x}", unit)?;
                 }
-                start = i + 1;
+            } else if esc == U || esc == H {
+                write!(self, "\\u{:04x}", c as u32)?;
+            } else {
+                self.out.write_str(match esc {
+                    B => "\\b",
+                    T => "\\t",
+                    N => "\\n",
+                    F => "\\f",
+                    R => "\\r",
+                    Q => "\\\"",
+                    A => "\\'",
+                    _ => "\\\\",
+                })?;
             }
+            i += len;
+            start = i;
         }
         if start != s.len() {
-            self.out.write_all(s[start..].as_bytes())?;
-        }
-        self.out.write_all(b"\"")
-    }
-
-    fn encode_number(&mut self, f: f64) -> io::Result<()> {
-        if f.is_infinite() {
-            Err(io::Error::new(
-                io::ErrorKind::Other,
-                "JSON cannot represent inf",
-            ))
-        } else if f.is_nan() {
-            Err(io::Error::new(
-                io::ErrorKind::Other,
-                "JSON cannot represent NaN",
-            ))
+            self.out.write_str(&s[start..])?;
+        }
+        self.out.write_str(quote)
+    }
+
+    fn encode_non_finite(&mut self, f: f64) -> Result<(), JsonGenerateError> {
+        let lit = if f.is_nan() {
+            "NaN"
+        } else if f > 0.0 {
+            "Infinity"
         } else {
-            write!(self.out, "{}", f)
+            "-Infinity"
+        };
+        match self.non_finite {
+            NonFinitePolicy::Error if self.json5 => self.out.write_str(lit),
+            NonFinitePolicy::Error => {
+                let msg = if f.is_nan() {
+                    "JSON cannot represent NaN"
+                } else {
+                    "JSON cannot represent inf"
+                };
+                Err(JsonGenerateError::new(
+                    JsonGenerateErrorKind::InvalidValue,
+                    msg,
+                ))
+            }
+            NonFinitePolicy::WriteNull => self.out.write_str("null"),
+            NonFinitePolicy::WriteAsString => {
+                self.out.write_str("\"")?;
+                self.out.write_str(lit)?;
+                self.out.write_str("\"")
+            }
+            NonFinitePolicy::WriteLiteral => self.out.write_str(lit),
         }
     }
 
-    fn encode_array(&mut self, array: &[JsonValue]) -> io::Result<()> {
-        self.out.write_all(b"[")?;
-        let mut first = true;
-        for elem in array.iter() {
-            if first {
-                first = false;
+    fn encode_number(&mut self, f: f64) -> Result<(), JsonGenerateError> {
+        if !f.is_finite() {
+            return self.encode_non_finite(f);
+        }
+        if f == 0.0 {
+            // -0 is written as 0 as well as JavaScript
+            return match self.precision {
+                Some(p) if !self.whole_numbers_as_integers => write!(self, "{:.*}", p, 0.0),
+                _ => self.out.write_str("0"),
+            };
+        }
+        // `f64::fract` is not available in `core`. All numbers larger than 2^52 are whole numbers
+        if self.whole_numbers_as_integers && (f.abs() >= 4503599627370496.0 || f == f as i64 as f64)
+        {
+            return write!(self, "{:.0}", f);
+        }
+        if let Some(p) = self.precision {
+            return match round_half_up(f.abs(), p) {
+                Some(s) if f < 0.0 => write!(self, "-{}", s),
+                Some(s) => self.out.write_str(&s),
+                None => write!(self, "{:.*}", p, f),
+            };
+        }
+        self.encode_shortest_number(f)
+    }
+
+    // Write the number in the same format as Number::toString in ECMAScript.
+    // https://tc39.es/ecma262/#sec-numeric-types-number-tostring
+    fn encode_shortest_number(&mut self, f: f64) -> Result<(), JsonGenerateError> {
+        const ZEROS: &str = "00000000000000000000";
+
+        // Integers less than 2^53 are exact and written with all digits
+        let abs = f.abs();
+        if abs < 9007199254740992.0 && abs == abs as u64 as f64 {
+            let mut buf = NumberBuf::new();
+            write!(buf, "{}", f as i64).unwrap();
+            return self.out.write_str(buf.as_str());
+        }
+
+        // `{:e}` gives the shortest digits which can be round-tripped (e.g. "1.2345e2")
+        let mut buf = NumberBuf::new();
+        write!(buf, "{:e}", abs).unwrap();
+        let s = buf.as_str();
+        let idx = s.bytes().position(|b| b == b'e').unwrap();
+        let (int, frac) = (&s[..1], s.get(2..idx).unwrap_or(""));
+        let exp = s[idx + 1..].bytes().fold(0, |acc, b| {
+            if b == b'-' {
+                acc
             } else {
-                self.out.write_all(b",")?;
+                acc * 10 + i32::from(b - b'0')
+            }
+        });
+        // The value is 0.{int}{frac} * 10^n and k is the number of the digits
+        let n = if s.as_bytes()[idx + 1] == b'-' {
+            -exp
+        } else {
+            exp
+        } + 1;
+        let even;
+        let (int, frac) = match round_half_to_even(abs, int, frac, n) {
+            Some(digits) => {
+                even = digits;
+                even.as_str().split_at(1)
+            }
+            None => (int, frac),
+        };
+        let k = frac.len() as i32 + 1;
+
+        // Build the output in the buffer and write it at once. The longest output is like "-1.2345678901234567e+308"
+        let mut out = NumberBuf::new();
+        if f < 0.0 {
+            out.write_str("-")?;
+        }
+        if k <= n && n <= 21 {
+            out.write_str(int)?;
+            out.write_str(frac)?;
+            out.write_str(&ZEROS[..(n - k) as usize])?;
+        } else if 0 < n && n <= 21 {
+            let (before, after) = frac.split_at(n as usize - 1);
+            out.write_str(int)?;
+            out.write_str(before)?;
+            out.write_str(".")?;
+            out.write_str(after)?;
+        } else if -6 < n && n <= 0 {
+            out.write_str("0.")?;
+            out.write_str(&ZEROS[..-n as usize])?;
+            out.write_str(int)?;
+            out.write_str(frac)?;
+        } else {
+            out.write_str(int)?;
+            if !frac.is_empty() {
+                out.write_str(".")?;
+                out.write_str(frac)?;
+            }
+            write!(out, "e{:+}", n - 1)?;
+        }
+        self.out.write_str(out.as_str())
+    }
+
+    // Separator of elements of array and object
+    fn comma(&self) -> &'static str {
+        if self.inline {
+            ", "
+        } else {
+            ","
+        }
+    }
+
+    fn encode_array(&mut self, array: &[JsonValue]) -> Result<(), JsonGenerateError> {
+        self.write_punct("[")?;
+        for (i, elem) in array.iter().enumerate() {
+            if i > 0 {
+                self.write_punct(self.comma())?;
             }
-            self.encode(elem)?;
+            let ret = if self.redaction.is_none() {
+                self.encode(elem) // Encode directly without a closure when nothing is redacted
+            } else {
+                self.child(Segment::Index(i), |gen| gen.encode(elem))
+            };
+            ret.map_err(|e| e.at_index(i))?;
         }
-        self.out.write_all(b"]")
+        self.write_punct("]")
     }
 
-    fn encode_object(&mut self, m: &HashMap<String, JsonValue>) -> io::Result<()> {
-        self.out.write_all(b"{")?;
+    fn encode_object(&mut self, m: &BTreeMap<String, JsonValue>) -> Result<(), JsonGenerateError> {
+        self.write_punct("{")?;
+        if let Some(entries) = self.sorted_entries(m) {
+            self.encode_entries(entries.into_iter())?;
+        } else {
+            self.encode_entries(m.iter())?;
+        }
+        self.write_punct("}")
+    }
+
+    fn encode_entries<'v>(
+        &mut self,
+        entries: impl Iterator<Item = (&'v String, &'v JsonValue)>,
+    ) -> Result<(), JsonGenerateError> {
         let mut first = true;
-        for (k, v) in m {
+        for (k, v) in entries {
             if first {
                 first = false;
             } else {
-                self.out.write_all(b",")?;
+                self.write_punct(self.comma())?;
             }
-            self.encode_string(k)?;
-            self.out.write_all(b":")?;
-            self.encode(v)?;
+            self.encode_key(k)?;
+            self.write_punct(self.style.colon(self.inline))?;
+            let ret = if self.redaction.is_none() {
+                self.encode(v)
+            } else {
+                self.child(Segment::Key(k), |gen| gen.encode(v))
+            };
+            ret.map_err(|e| e.at_key(k))?;
         }
-        self.out.write_all(b"}")
+        Ok(())
     }
 
-    fn encode(&mut self, value: &JsonValue) -> io::Result<()> {
+    fn encode(&mut self, value: &JsonValue) -> Result<(), JsonGenerateError> {
+        if self.color.is_some() {
+            return self.encode_colored(value);
+        }
         match value {
             JsonValue::Number(n) => self.encode_number(*n),
-            JsonValue::Boolean(b) => self.out.write_all(if *b { b"true" } else { b"false" }),
+            JsonValue::Boolean(b) => self.out.write_str(if *b { "true" } else { "false" }),
             JsonValue::String(s) => self.encode_string(s),
-            JsonValue::Null => self.out.write_all(b"null"),
+            JsonValue::Null => self.out.write_str("null"),
             JsonValue::Array(a) => self.encode_array(a),
             JsonValue::Object(o) => self.encode_object(o),
         }
     }
 
-    fn write_indent(&mut self, indent: &str, level: usize) -> io::Result<()> {
+    // Separated from `encode` to keep the path without color small
+    #[inline(never)]
+    fn encode_colored(&mut self, value: &JsonValue) -> Result<(), JsonGenerateError> {
+        match value {
+            JsonValue::Number(n) => self.colored(|p| p.number, |gen| gen.encode_number(*n)),
+            JsonValue::Boolean(b) => self.colored(
+                |p| p.boolean,
+                |gen| gen.out.write_str(if *b { "true" } else { "false" }),
+            ),
+            JsonValue::String(s) => self.colored(|p| p.string, |gen| gen.encode_string(s)),
+            JsonValue::Null => self.colored(|p| p.null, |gen| gen.out.write_str("null")),
+            JsonValue::Array(a) => self.encode_array(a),
+            JsonValue::Object(o) => self.encode_object(o),
+        }
+    }
+
+    fn write_indent(&mut self, indent: &str, level: usize) -> Result<(), JsonGenerateError> {
         for _ in 0..level {
-            self.out.write_all(indent.as_bytes())?;
+            self.out.write_str(indent)?;
         }
         Ok(())
     }
 
-    fn format_array(&mut self, array: &[JsonValue], indent: &str, level: usize) -> io::Result<()> {
-        if array.is_empty() {
-            return self.out.write_all(b"[]");
+    fn write_newline(&mut self) -> Result<(), JsonGenerateError> {
+        self.out.write_str(self.style.newline())
+    }
+
+    // Write the comma following an element if necessary and the newline. They are written at once when not colored
+    fn write_line_end(&mut self, comma: bool) -> Result<(), JsonGenerateError> {
+        if comma && self.color.is_none() {
+            return self
+                .out
+                .write_str(if self.style.crlf { ",\r\n" } else { ",\n" });
+        }
+        if comma {
+            self.write_punct(",")?;
         }
+        self.write_newline()
+    }
 
-        self.out.write_all(b"[\n")?;
-        let mut first = true;
-        for elem in array.iter() {
-            if first {
-                first = false;
-            } else {
-                self.out.write_all(b",\n")?;
+    fn write_empty(&mut self, open: &str, close: &str) -> Result<(), JsonGenerateError> {
+        self.write_punct(open)?;
+        if self.style.space_in_empty {
+            self.out.write_str(" ")?;
+        }
+        self.write_punct(close)
+    }
+
+    // Count the number of characters written by the function. Returns `None` when the count exceeds the limit
+    fn measure<F>(&self, limit: usize, f: F) -> Option<usize>
+    where
+        F: FnOnce(&mut JsonGenerator<'a, Measure>) -> Result<(), JsonGenerateError>,
+    {
+        let mut gen = JsonGenerator {
+            out: Buffered::new(Measure { len: 0, limit }, 0),
+            indent: None,
+            precision: self.precision,
+            whole_numbers_as_integers: self.whole_numbers_as_integers,
+            ascii_only: self.ascii_only,
+            html_safe: self.html_safe,
+            non_finite: self.non_finite,
+            style: self.style,
+            key_order: KeyOrder::Unordered, // Order of keys does not affect the length
+            max_width: None,
+            color: None, // Escape sequences are not counted in the width
+            redaction: self.redaction.clone(),
+            json5: self.json5,
+            single_quotes: self.single_quotes,
+            inline: true,
+        };
+        f(&mut gen).ok()?;
+        Some(gen.out.out.len)
+    }
+
+    // Write the array or object in one line when it fits in the max width. `col` is the column where the value starts
+    // and `suffix` is the number of characters following the value in the line. Returns `false` when the value was
+    // not written.
+    fn format_inline(
+        &mut self,
+        value: &JsonValue,
+        col: usize,
+        suffix: usize,
+    ) -> Result<bool, JsonGenerateError> {
+        let max_width = match self.max_width {
+            Some(w) => w,
+            None => return Ok(false),
+        };
+        match value {
+            JsonValue::Array(a) if !a.is_empty() => {}
+            JsonValue::Object(o) if !o.is_empty() => {}
+            _ => return Ok(false),
+        }
+        let limit = max_width.saturating_sub(col + suffix);
+        if self.measure(limit, |gen| gen.encode(value)).is_none() {
+            return Ok(false);
+        }
+
+        self.inline = true;
+        let result = self.encode(value);
+        self.inline = false;
+        result.map(|_| true)
+    }
+
+    // Write the value at the level. Arrays and objects are written in one line when they fit in the max width. Other
+    // values are always encoded directly
+    fn format_element(
+        &mut self,
+        value: &JsonValue,
+        indent: &str,
+        level: usize,
+        col: usize,
+        suffix: usize,
+    ) -> Result<(), JsonGenerateError> {
+        match value {
+            JsonValue::Array(_) | JsonValue::Object(_) => {
+                if !self.format_inline(value, col, suffix)? {
+                    self.format(value, indent, level)?;
+                }
+                Ok(())
             }
+            _ => self.encode(value),
+        }
+    }
+
+    fn format_array(
+        &mut self,
+        array: &[JsonValue],
+        indent: &str,
+        level: usize,
+    ) -> Result<(), JsonGenerateError> {
+        if array.is_empty() {
+            return self.write_empty("[", "]");
+        }
+
+        self.write_punct("[")?;
+        self.write_newline()?;
+        let col = indent.chars().count() * (level + 1);
+        for (i, elem) in array.iter().enumerate() {
+            let comma = i + 1 < array.len() || self.json5; // JSON5 allows trailing comma
             self.write_indent(indent, level + 1)?;
-            self.format(elem, indent, level + 1)?;
+            let ret = if self.redaction.is_none() {
+                self.format_element(elem, indent, level + 1, col, comma as usize)
+            } else {
+                self.child(Segment::Index(i), |gen| {
+                    gen.format_element(elem, indent, level + 1, col, comma as usize)
+                })
+            };
+            ret.map_err(|e| e.at_index(i))?;
+            self.write_line_end(comma)?;
         }
-        self.out.write_all(b"\n")?;
         self.write_indent(indent, level)?;
-        self.out.write_all(b"]")
+        self.write_punct("]")
     }
 
     fn format_object(
         &mut self,
-        m: &HashMap<String, JsonValue>,
+        m: &BTreeMap<String, JsonValue>,
         indent: &str,
         level: usize,
-    ) -> io::Result<()> {
+    ) -> Result<(), JsonGenerateError> {
         if m.is_empty() {
-            return self.out.write_all(b"{}");
+            return self.write_empty("{", "}");
         }
 
-        self.out.write_all(b"{\n")?;
-        let mut first = true;
-        for (k, v) in m {
-            if first {
-                first = false;
-            } else {
-                self.out.write_all(b",\n")?;
-            }
-            self.write_indent(indent, level + 1)?;
-            self.encode_string(k)?;
-            self.out.write_all(b": ")?;
-            self.format(v, indent, level + 1)?;
+        self.write_punct("{")?;
+        self.write_newline()?;
+        if let Some(entries) = self.sorted_entries(m) {
+            self.format_entries(entries.into_iter(), m.len(), indent, level)?;
+        } else {
+            self.format_entries(m.iter(), m.len(), indent, level)?;
         }
-        self.out.write_all(b"\n")?;
         self.write_indent(indent, level)?;
-        self.out.write_all(b"}")
+        self.write_punct("}")
+    }
+
+    fn format_entries<'v>(
+        &mut self,
+        entries: impl Iterator<Item = (&'v String, &'v JsonValue)>,
+        len: usize,
+        indent: &str,
+        level: usize,
+    ) -> Result<(), JsonGenerateError> {
+        for (i, (k, v)) in entries.enumerate() {
+            let comma = i + 1 < len || self.json5; // JSON5 allows trailing comma
+            self.write_indent(indent, level + 1)?;
+            self.encode_key(k)?;
+            let colon = self.style.colon(true);
+            self.write_punct(colon)?;
+
+            let mut col = 0;
+            if self.max_width.is_some() {
+                let key = self.measure(usize::MAX, |gen| gen.encode_key(k));
+                col = indent.chars().count() * (level + 1) + key.unwrap_or(0) + colon.len();
+            }
+            let ret = if self.redaction.is_none() {
+                self.format_element(v, indent, level + 1, col, comma as usize)
+            } else {
+                self.child(Segment::Key(k), |gen| {
+                    gen.format_element(v, indent, level + 1, col, comma as usize)
+                })
+            };
+            ret.map_err(|e| e.at_key(k))?;
+
+            self.write_line_end(comma)?;
+        }
+        Ok(())
     }
 
-    fn format(&mut self, value: &JsonValue, indent: &str, level: usize) -> io::Result<()> {
+    fn format(
+        &mut self,
+        value: &JsonValue,
+        indent: &str,
+        level: usize,
+    ) -> Result<(), JsonGenerateError> {
         match value {
-            JsonValue::Number(n) => self.encode_number(*n),
-            JsonValue::Boolean(b) => self.out.write_all(if *b { b"true" } else { b"false" }),
-            JsonValue::String(s) => self.encode_string(s),
-            JsonValue::Null => self.out.write_all(b"null"),
             JsonValue::Array(a) => self.format_array(a, indent, level),
             JsonValue::Object(o) => self.format_object(o, indent, level),
+            _ => self.encode(value),
         }
     }
 
@@ -261,6 +1833,10 @@ impl<'indent, W: Write> JsonGenerator<'indent, W> {
     /// This method serializes the value without indentation by default. But after setting an indent string via
     /// [`JsonGenerator::indent`], this method will use the indent for elements of array and object.
     ///
+    /// Numbers are written in the same format as `Number.prototype.toString` in JavaScript. The shortest digits which
+    /// can be parsed back to the same value are used and exponent notation is used for very large or very small
+    /// numbers. Infinity and NaN cannot be written since JSON cannot represent them.
+    ///
     /// ```
     /// use tinyjson::{JsonGenerator, JsonValue};
     ///
@@ -282,13 +1858,128 @@ impl<'indent, W: Write> JsonGenerator<'indent, W> {
     ///   3
     /// ]");
     /// ```
-    pub fn generate(&mut self, value: &JsonValue) -> io::Result<()> {
-        if let Some(indent) = &self.indent {
-            self.format(value, indent, 0)
+    pub fn generate(&mut self, value: &JsonValue) -> Result<(), W::Error> {
+        let ret = self.generate_value(value);
+        // Write the buffered output even on error as if it was not buffered
+        let flushed = self.out.flush();
+        ret.and(flushed).map_err(|err| self.out.error(err))
+    }
+
+    fn generate_value(&mut self, value: &JsonValue) -> Result<(), JsonGenerateError> {
+        if let Some(indent) = self.indent {
+            self.format_element(value, indent, 0, 0, 0)?;
+        } else {
+            self.encode(value)?;
+        }
+        if self.style.trailing_newline {
+            self.write_newline()?;
+        }
+        Ok(())
+    }
+
+    /// Write the output remaining in the internal buffer and return the underlying output. The buffer is always
+    /// flushed at the end of [`JsonGenerator::generate`], so this method is only necessary to get the output back.
+    /// See [`JsonGenerator::buffer_size`].
+    ///
+    /// ```
+    /// use tinyjson::{JsonGenerator, JsonValue};
+    ///
+    /// let v = JsonValue::from(vec![1.0.into(), 2.0.into()]);
+    /// let mut gen = JsonGenerator::new(vec![]);
+    /// gen.generate(&v).unwrap();
+    /// gen.generate(&v).unwrap();
+    /// let buf = gen.into_inner().unwrap();
+    /// assert_eq!(String::from_utf8(buf).unwrap(), "[1,2][1,2]");
+    /// ```
+    pub fn into_inner(mut self) -> Result<W, W::Error> {
+        if let Err(err) = self.out.flush() {
+            return Err(self.out.error(err));
+        }
+        Ok(self.out.out)
+    }
+
+    // The following methods are building blocks for `JsonWriter`
+
+    pub(crate) fn error(&mut self, err: JsonGenerateError) -> W::Error {
+        self.out.error(err)
+    }
+
+    pub(crate) fn flush(&mut self) -> Result<(), JsonGenerateError> {
+        self.out.flush()
+    }
+
+    pub(crate) fn write_open(&mut self, open: &str) -> Result<(), JsonGenerateError> {
+        self.write_punct(open)
+    }
+
+    // Write the separator before an element of array or object at the level
+    pub(crate) fn write_separator(
+        &mut self,
+        first: bool,
+        level: usize,
+    ) -> Result<(), JsonGenerateError> {
+        if !first {
+            self.write_punct(",")?;
+        }
+        if let Some(indent) = self.indent {
+            self.write_newline()?;
+            self.write_indent(indent, level)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn write_key(&mut self, key: &str) -> Result<(), JsonGenerateError> {
+        self.encode_key(key)?;
+        self.write_punct(self.style.colon(self.indent.is_some()))
+    }
+
+    pub(crate) fn write_value(
+        &mut self,
+        value: &JsonValue,
+        level: usize,
+    ) -> Result<(), JsonGenerateError> {
+        if let Some(indent) = self.indent {
+            self.format(value, indent, level)
         } else {
             self.encode(value)
         }
     }
+
+    pub(crate) fn write_string(&mut self, s: &str) -> Result<(), JsonGenerateError> {
+        self.colored(|p| p.string, |gen| gen.encode_string(s))
+    }
+
+    // Close the array or object at the level. `empty` is `true` when it has no element
+    pub(crate) fn write_close(
+        &mut self,
+        close: &str,
+        empty: bool,
+        level: usize,
+    ) -> Result<(), JsonGenerateError> {
+        if let Some(indent) = self.indent {
+            if empty {
+                if self.style.space_in_empty {
+                    self.out.write_str(" ")?;
+                }
+            } else {
+                if self.json5 {
+                    self.write_punct(",")?;
+                }
+                self.write_newline()?;
+                self.write_indent(indent, level)?;
+            }
+        }
+        self.write_punct(close)
+    }
+
+    pub(crate) fn finish(mut self) -> Result<W, W::Error> {
+        if self.style.trailing_newline {
+            if let Err(err) = self.write_newline() {
+                return Err(self.out.error(err));
+            }
+        }
+        self.into_inner()
+    }
 }
 
 /// Serialize the given `JsonValue` value to `String` without indentation. This method is almost identical to
@@ -302,12 +1993,9 @@ impl<'indent, W: Write> JsonGenerator<'indent, W> {
 /// assert_eq!(s, "[1,2,3]");
 /// ```
 pub fn stringify(value: &JsonValue) -> JsonGenerateResult {
-    let mut to = Vec::new();
-    let mut gen = JsonGenerator::new(&mut to);
-    gen.generate(value).map_err(|err| JsonGenerateError {
-        msg: format!("{}", err),
-    })?;
-    Ok(String::from_utf8(to).unwrap())
+    let mut to = String::new();
+    JsonGenerator::new(FmtWriter(&mut to)).generate(value)?;
+    Ok(to)
 }
 
 /// Serialize the given `JsonValue` value to `String` with 2-spaces indentation. This method is almost identical to
@@ -321,10 +2009,31 @@ pub fn stringify(value: &JsonValue) -> JsonGenerateResult {
 /// assert_eq!(s, "[\n  1,\n  2,\n  3\n]");
 /// ```
 pub fn format(value: &JsonValue) -> JsonGenerateResult {
-    let mut to = Vec::new();
-    let mut gen = JsonGenerator::new(&mut to).indent("  ");
-    gen.generate(value).map_err(|err| JsonGenerateError {
-        msg: format!("{}", err),
-    })?;
-    Ok(String::from_utf8(to).unwrap())
+    let mut to = String::new();
+    JsonGenerator::new(FmtWriter(&mut to))
+        .indent("  ")
+        .generate(value)?;
+    Ok(to)
+}
+
+/// Serialize the given `JsonValue` value to `String` in the canonical form defined by
+/// [RFC 8785 JSON Canonicalization Scheme (JCS)](https://www.rfc-editor.org/rfc/rfc8785). The same value is always
+/// serialized to the same byte sequence so the result can be used for hashing and signing. See
+/// [`JsonGenerator::canonical`] for the details.
+///
+/// ```
+/// use tinyjson::JsonValue;
+///
+/// let v: JsonValue = r#"{"\u20ac": 1, "\r": 2, "1": 3, "\ud83d\ude00": 4, "\ufb33": 5}"#.parse().unwrap();
+/// let s = tinyjson::canonicalize(&v).unwrap();
+/// assert_eq!(s, "{\"\\r\":2,\"1\":3,\"\u{20ac}\":1,\"\u{1f600}\":4,\"\u{fb33}\":5}");
+///
+/// assert!(tinyjson::canonicalize(&JsonValue::from(f64::NAN)).is_err());
+/// ```
+pub fn canonicalize(value: &JsonValue) -> JsonGenerateResult {
+    let mut to = String::new();
+    JsonGenerator::new(FmtWriter(&mut to))
+        .canonical()
+        .generate(value)?;
+    Ok(to)
 }
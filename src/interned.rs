@@ -0,0 +1,241 @@
+use crate::json_value::JsonValue;
+use crate::parser::{JsonParseError, JsonParser};
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::ops::Index;
+
+/// Table of object keys shared by [`InternedJsonValue`] values. Each distinct key is allocated once and objects hold
+/// reference-counted pointers to it. The same table can be used for parsing many documents such as lines of JSON
+/// Lines. Keys are kept in the table until it is dropped.
+///
+/// ```
+/// use tinyjson::{JsonParser, KeyInterner};
+///
+/// let mut keys = KeyInterner::new();
+/// for line in [r#"{"id": 1, "name": "a"}"#, r#"{"id": 2, "name": "b"}"#] {
+///     JsonParser::new(line.chars()).parse_interned(&mut keys).unwrap();
+/// }
+/// assert_eq!(keys.len(), 2);
+/// ```
+#[derive(Default)]
+pub struct KeyInterner {
+    keys: BTreeSet<Rc<str>>,
+}
+
+impl KeyInterner {
+    /// Create a new empty `KeyInterner` instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the number of distinct keys in the table.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Return `true` when the table has no key.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    fn intern(&mut self, key: &str) -> Rc<str> {
+        if let Some(k) = self.keys.get(key) {
+            return k.clone();
+        }
+        let k: Rc<str> = Rc::from(key);
+        self.keys.insert(k.clone());
+        k
+    }
+}
+
+/// JSON value whose object keys are interned with [`KeyInterner`]. This is parsed by [`JsonParser::parse_interned`].
+///
+/// Unlike [`JsonValue`], keys of objects are `Rc<str>` values shared by all objects which have the same key. This
+/// reduces the memory usage of a large document which has many objects of the same shape, such as an array of
+/// records. Objects can be looked up by `&str` keys with `[]` operator or `BTreeMap::get`. Convert the value into
+/// [`JsonValue`] with `From` trait to use other APIs such as [`JsonValue::query`] and [`JsonValue::stringify`].
+///
+/// Note that this type is not `Send` since `Rc` is not.
+///
+/// ```
+/// use tinyjson::{InternedJsonValue, JsonParser, JsonValue, KeyInterner};
+///
+/// let mut keys = KeyInterner::new();
+/// let v = JsonParser::new(r#"[{"id": 1}, {"id": 2}]"#.chars())
+///     .parse_interned(&mut keys)
+///     .unwrap();
+/// assert_eq!(v[1]["id"], InternedJsonValue::Number(2.0));
+///
+/// // All objects share the key "id"
+/// assert_eq!(keys.len(), 1);
+///
+/// let v = JsonValue::from(v);
+/// assert_eq!(v.stringify().unwrap(), r#"[{"id":1},{"id":2}]"#);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum InternedJsonValue {
+    /// Number type value.
+    Number(f64),
+    /// Boolean type value.
+    Boolean(bool),
+    /// String type value.
+    String(String),
+    /// Null type value.
+    Null,
+    /// Array type value.
+    Array(Vec<InternedJsonValue>),
+    /// Object type value. Its keys are shared through [`KeyInterner`].
+    Object(BTreeMap<Rc<str>, InternedJsonValue>),
+}
+
+impl InternedJsonValue {
+    fn type_name(&self) -> &'static str {
+        match self {
+            InternedJsonValue::Number(_) => "number",
+            InternedJsonValue::Boolean(_) => "boolean",
+            InternedJsonValue::String(_) => "string",
+            InternedJsonValue::Null => "null",
+            InternedJsonValue::Array(_) => "array",
+            InternedJsonValue::Object(_) => "object",
+        }
+    }
+}
+
+/// Access the element value of the key of object. Like [`JsonValue`], it will panic when the value is not an object
+/// or when the key does not exist in the object.
+impl<'a> Index<&'a str> for InternedJsonValue {
+    type Output = InternedJsonValue;
+
+    fn index(&self, key: &'a str) -> &Self::Output {
+        let obj = match self {
+            InternedJsonValue::Object(o) => o,
+            _ => panic!(
+                "Attempted to access to an object with key '{}' but actually it was {}",
+                key,
+                self.type_name(),
+            ),
+        };
+
+        match obj.get(key) {
+            Some(json) => json,
+            None => panic!("Key '{}' was not found in object", key),
+        }
+    }
+}
+
+/// Access the element value of the index of array. Like [`JsonValue`], it will panic when the value is not an array
+/// or when the index is out of bounds.
+impl Index<usize> for InternedJsonValue {
+    type Output = InternedJsonValue;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        let array = match self {
+            InternedJsonValue::Array(a) => a,
+            _ => panic!(
+                "Attempted to access to an array with index {} but actually the value was {}",
+                index,
+                self.type_name(),
+            ),
+        };
+        &array[index]
+    }
+}
+
+/// Convert the interned value into [`JsonValue`]. Each key of objects is copied into a new `String`.
+impl From<InternedJsonValue> for JsonValue {
+    fn from(v: InternedJsonValue) -> Self {
+        match v {
+            InternedJsonValue::Number(n) => JsonValue::Number(n),
+            InternedJsonValue::Boolean(b) => JsonValue::Boolean(b),
+            InternedJsonValue::String(s) => JsonValue::String(s),
+            InternedJsonValue::Null => JsonValue::Null,
+            InternedJsonValue::Array(a) => {
+                JsonValue::Array(a.into_iter().map(Into::into).collect())
+            }
+            InternedJsonValue::Object(o) => JsonValue::Object(
+                o.into_iter()
+                    .map(|(k, v)| (k.to_string(), v.into()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl<I: Iterator<Item = char>> JsonParser<I> {
+    /// Run the parser to parse one JSON value whose object keys are interned with the given [`KeyInterner`]. Options
+    /// of the parser such as [`JsonParser::strict_numbers`] are effective as well as [`JsonParser::parse`]. See
+    /// [`InternedJsonValue`] for more details.
+    pub fn parse_interned(
+        &mut self,
+        keys: &mut KeyInterner,
+    ) -> Result<InternedJsonValue, JsonParseError> {
+        let mut key = String::new();
+        let v = self.parse_interned_any(keys, &mut key)?;
+
+        if let Some(c) = self.next() {
+            return self.err(format!(
+                "Expected EOF but got character '{}'",
+                c.escape_debug(),
+            ));
+        }
+
+        Ok(v)
+    }
+
+    // `key` is a buffer for parsing keys before interning them
+    fn parse_interned_any(
+        &mut self,
+        keys: &mut KeyInterner,
+        key: &mut String,
+    ) -> Result<InternedJsonValue, JsonParseError> {
+        match self.peek()? {
+            '[' => {
+                self.consume()?;
+                let mut a = Vec::new();
+                if self.peek()? == ']' {
+                    self.consume()?;
+                    return Ok(InternedJsonValue::Array(a));
+                }
+                loop {
+                    a.push(self.parse_interned_any(keys, key)?);
+                    match self.consume()? {
+                        ',' => {}
+                        ']' => return Ok(InternedJsonValue::Array(a)),
+                        c => {
+                            return self.err(format!(
+                                "',' or ']' is expected for array but actually found '{}'",
+                                c
+                            ))
+                        }
+                    }
+                }
+            }
+            '{' => {
+                self.consume()?;
+                let mut m = BTreeMap::new();
+                if self.peek()? == '}' {
+                    self.consume()?;
+                    return Ok(InternedJsonValue::Object(m));
+                }
+                loop {
+                    self.parse_key(key)?;
+                    let k = keys.intern(key);
+                    m.insert(k, self.parse_interned_any(keys, key)?);
+                    if !self.parse_object_delim()? {
+                        return Ok(InternedJsonValue::Object(m));
+                    }
+                }
+            }
+            _ => Ok(match self.parse_any()? {
+                JsonValue::Number(n) => InternedJsonValue::Number(n),
+                JsonValue::Boolean(b) => InternedJsonValue::Boolean(b),
+                JsonValue::String(s) => InternedJsonValue::String(s),
+                JsonValue::Null => InternedJsonValue::Null,
+                JsonValue::Array(_) | JsonValue::Object(_) => unreachable!(),
+            }),
+        }
+    }
+}
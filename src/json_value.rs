@@ -1,10 +1,17 @@
-use crate::generator::{format, stringify, JsonGenerateResult, JsonGenerator};
+use crate::generator::{
+    canonicalize, format, stringify, ByteCount, FmtWriter, JsonGenerateError, JsonGenerateResult,
+    JsonGenerator, NonFinitePolicy,
+};
 use crate::query::{JsonQuery, JsonQueryMut};
-use std::collections::HashMap;
-use std::convert::TryFrom;
-use std::fmt;
+use crate::summary::JsonSummary;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+use core::ops::{Index, IndexMut};
+#[cfg(feature = "std")]
 use std::io;
-use std::ops::{Index, IndexMut};
 
 const NULL: () = ();
 
@@ -42,7 +49,7 @@ pub enum JsonValue {
     /// Array type value.
     Array(Vec<JsonValue>),
     /// Object type value.
-    Object(HashMap<String, JsonValue>),
+    Object(BTreeMap<String, JsonValue>),
 }
 
 /// Trait to access to inner value of `JsonValue` as reference.
@@ -71,7 +78,7 @@ impl_inner_ref!(bool, Boolean(b) => b);
 impl_inner_ref!(String, String(s) => s);
 impl_inner_ref!((), Null => &NULL);
 impl_inner_ref!(Vec<JsonValue>, Array(a) => a);
-impl_inner_ref!(HashMap<String, JsonValue>, Object(h) => h);
+impl_inner_ref!(BTreeMap<String, JsonValue>, Object(h) => h);
 
 /// Trait to access to inner value of `JsonValue` as mutable reference.
 ///
@@ -98,7 +105,7 @@ impl_inner_ref_mut!(f64, Number(n) => n);
 impl_inner_ref_mut!(bool, Boolean(b) => b);
 impl_inner_ref_mut!(String, String(s) => s);
 impl_inner_ref_mut!(Vec<JsonValue>, Array(a) => a);
-impl_inner_ref_mut!(HashMap<String, JsonValue>, Object(h) => h);
+impl_inner_ref_mut!(BTreeMap<String, JsonValue>, Object(h) => h);
 
 // Note: matches! is available from Rust 1.42
 macro_rules! is_xxx {
@@ -242,15 +249,15 @@ impl JsonValue {
         ///
         /// ```
         /// use tinyjson::JsonValue;
-        /// use std::collections::HashMap;
+        /// use std::collections::BTreeMap;
         ///
-        /// let v = JsonValue::from(HashMap::new());
+        /// let v = JsonValue::from(BTreeMap::new());
         /// assert!(v.is_object());
         /// let v = JsonValue::from(vec![]);
         /// assert!(!v.is_object());
         ///
         /// // matches! macro may be better choice
-        /// let mut m = HashMap::new();
+        /// let mut m = BTreeMap::new();
         /// m.insert("hello".to_string(), "world".to_string().into());
         /// let v = JsonValue::from(m);
         /// assert!(matches!(&v, JsonValue::Object(o) if o.contains_key("hello")));
@@ -284,6 +291,7 @@ impl JsonValue {
     /// v.write_to(&mut bytes).unwrap();
     /// assert_eq!(&String::from_utf8(bytes).unwrap(), "[1,true,\"str\"]");
     /// ```
+    #[cfg(feature = "std")]
     pub fn write_to<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
         JsonGenerator::new(w).generate(self)
     }
@@ -321,10 +329,58 @@ impl JsonValue {
     ///   \"str\"
     /// ]");
     /// ```
+    #[cfg(feature = "std")]
     pub fn format_to<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
         JsonGenerator::new(w).indent("  ").generate(self)
     }
 
+    /// Compute the number of bytes of the UTF-8 output of [`JsonValue::stringify`] without generating the output. The
+    /// result is exact since the same escaping and number formatting rules are applied. This is useful for
+    /// `Content-Length` header or checking the size limit of payloads. It fails when the value contains infinity or NaN.
+    ///
+    /// ```
+    /// use tinyjson::JsonValue;
+    ///
+    /// let v: JsonValue = r#"{"msg": "héllo\n", "n": [1.5, 1e300]}"#.parse().unwrap();
+    /// assert_eq!(v.serialized_len().unwrap(), v.stringify().unwrap().len());
+    /// ```
+    pub fn serialized_len(&self) -> Result<usize, JsonGenerateError> {
+        let mut gen = JsonGenerator::new(ByteCount(0));
+        gen.generate(self)?;
+        Ok(gen.into_inner()?.0)
+    }
+
+    /// Compute the number of bytes of the UTF-8 output with the indent string without generating the output. The
+    /// output is the same as [`JsonValue::format`] when the indent is two spaces. See [`JsonValue::serialized_len`]
+    /// for more details.
+    ///
+    /// ```
+    /// use tinyjson::JsonValue;
+    ///
+    /// let v: JsonValue = r#"{"msg": "héllo\n", "n": [1.5, 1e300]}"#.parse().unwrap();
+    /// assert_eq!(v.formatted_len("  ").unwrap(), v.format().unwrap().len());
+    /// assert_eq!(v.formatted_len("\t").unwrap(), 50);
+    /// ```
+    pub fn formatted_len(&self, indent: &str) -> Result<usize, JsonGenerateError> {
+        let mut gen = JsonGenerator::new(ByteCount(0)).indent(indent);
+        gen.generate(self)?;
+        Ok(gen.into_inner()?.0)
+    }
+
+    /// Convert this JSON value to `String` value in the canonical form defined by RFC 8785. See [`canonicalize`]
+    /// for more details.
+    ///
+    /// ```
+    /// use tinyjson::JsonValue;
+    ///
+    /// let v: JsonValue = r#"{"b": 1.0, "a": [true, null]}"#.parse().unwrap();
+    /// let s = v.canonicalize().unwrap();
+    /// assert_eq!(&s, r#"{"a":[true,null],"b":1}"#);
+    /// ```
+    pub fn canonicalize(&self) -> JsonGenerateResult {
+        canonicalize(self)
+    }
+
     /// Create a panic-safe JSON query for this value. It allows accessing the nested values by index/key/value
     /// easily via immutable reference.
     ///
@@ -383,22 +439,74 @@ impl JsonValue {
     pub fn query_mut(&mut self) -> JsonQueryMut<'_> {
         JsonQueryMut::new(self)
     }
+
+    /// Create a truncated summary of this value for logs and error messages. The summary is written by `Display` in one
+    /// line with limited length. See [`JsonSummary`] for more details.
+    ///
+    /// ```
+    /// use tinyjson::JsonValue;
+    ///
+    /// let v = JsonValue::from((0..1000).map(|i| JsonValue::from(i as f64)).collect::<Vec<_>>());
+    /// assert_eq!(v.summary().to_string(), "[0,1,2,3,4,5,6,7,… 992 more]");
+    /// assert_eq!(v.summary().max_items(2).to_string(), "[0,1,… 998 more]");
+    /// ```
+    pub fn summary(&self) -> JsonSummary<'_> {
+        JsonSummary::new(self)
+    }
+}
+
+/// Write the JSON value as JSON text. `{}` writes the minified text like [`JsonValue::stringify`] and `{:#}` writes
+/// the text with 2-spaces indentation like [`JsonValue::format`]. The text is written directly to the formatter without
+/// allocating an intermediate `String`.
+///
+/// ```
+/// use tinyjson::JsonValue;
+/// use std::fmt::Write;
+///
+/// let v: JsonValue = r#"{"foo": [1, true]}"#.parse().unwrap();
+/// assert_eq!(format!("{}", v), r#"{"foo":[1,true]}"#);
+/// assert_eq!(format!("{:#}", v), "{\n  \"foo\": [\n    1,\n    true\n  ]\n}");
+///
+/// // Any `fmt::Write` object can be the output
+/// let mut s = String::from("value: ");
+/// write!(s, "{}", v).unwrap();
+/// assert_eq!(s, r#"value: {"foo":[1,true]}"#);
+/// ```
+///
+/// Since `Display` must not fail, NaN and infinite numbers are written as `null` like [`NonFinitePolicy::WriteNull`].
+/// Use [`JsonValue::stringify`] or [`JsonGenerator`] to detect such numbers as errors.
+///
+/// ```
+/// use tinyjson::JsonValue;
+///
+/// let v = JsonValue::from(vec![f64::NAN.into(), f64::INFINITY.into()]);
+/// assert_eq!(v.to_string(), "[null,null]");
+/// ```
+impl fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pretty = f.alternate();
+        let mut gen = JsonGenerator::new(FmtWriter(f)).non_finite(NonFinitePolicy::WriteNull);
+        if pretty {
+            gen = gen.indent("  ");
+        }
+        gen.generate(self).map_err(|_| fmt::Error)
+    }
 }
 
 /// Access the element value of the key of object.
 ///
 /// ```
 /// use tinyjson::JsonValue;
-/// use std::collections::HashMap;
+/// use std::collections::BTreeMap;
 ///
-/// let mut m = HashMap::new();
+/// let mut m = BTreeMap::new();
 /// m.insert("foo".to_string(), 1.0.into());
 /// let v = JsonValue::from(m);
 /// let i = &v["foo"];
 /// assert_eq!(i, &JsonValue::Number(1.0));
 /// ```
 ///
-/// Like standard containers such as `Vec` or `HashMap`, it will panic when the given `JsonValue` value is not an object
+/// Like standard containers such as `Vec` or `BTreeMap`, it will panic when the given `JsonValue` value is not an object
 ///
 /// ```should_panic
 /// # use tinyjson::JsonValue;
@@ -410,8 +518,8 @@ impl JsonValue {
 ///
 /// ```should_panic
 /// # use tinyjson::JsonValue;
-/// # use std::collections::HashMap;
-/// let v = JsonValue::from(HashMap::new());
+/// # use std::collections::BTreeMap;
+/// let v = JsonValue::from(BTreeMap::new());
 /// let _ = &v["foo"]; // Panic
 /// ```
 ///
@@ -440,14 +548,15 @@ impl<'a> Index<&'a str> for JsonValue {
         let obj = match self {
             JsonValue::Object(o) => o,
             _ => panic!(
-                "Attempted to access to an object with key '{}' but actually it was {:?}",
-                key, self
+                "Attempted to access to an object with key '{}' but actually it was {}",
+                key,
+                self.summary()
             ),
         };
 
         match obj.get(key) {
             Some(json) => json,
-            None => panic!("Key '{}' was not found in {:?}", key, self),
+            None => panic!("Key '{}' was not found in {}", key, self.summary()),
         }
     }
 }
@@ -462,12 +571,12 @@ impl<'a> Index<&'a str> for JsonValue {
 /// assert_eq!(b, &JsonValue::Boolean(true));
 /// ```
 ///
-/// Like standard containers such as `Vec` or `HashMap`, it will panic when the given `JsonValue` value is not an array
+/// Like standard containers such as `Vec` or `BTreeMap`, it will panic when the given `JsonValue` value is not an array
 ///
 /// ```should_panic
 /// # use tinyjson::JsonValue;
-/// use std::collections::HashMap;
-/// let v = JsonValue::from(HashMap::new());
+/// use std::collections::BTreeMap;
+/// let v = JsonValue::from(BTreeMap::new());
 /// let _ = &v[0]; // Panic
 /// ```
 ///
@@ -485,8 +594,9 @@ impl Index<usize> for JsonValue {
         let array = match self {
             JsonValue::Array(a) => a,
             _ => panic!(
-                "Attempted to access to an array with index {} but actually the value was {:?}",
-                index, self,
+                "Attempted to access to an array with index {} but actually the value was {}",
+                index,
+                self.summary(),
             ),
         };
         &array[index]
@@ -497,16 +607,16 @@ impl Index<usize> for JsonValue {
 ///
 /// ```
 /// use tinyjson::JsonValue;
-/// use std::collections::HashMap;
+/// use std::collections::BTreeMap;
 ///
-/// let mut m = HashMap::new();
+/// let mut m = BTreeMap::new();
 /// m.insert("foo".to_string(), 1.0.into());
 /// let mut v = JsonValue::from(m);
 /// v["foo"] = JsonValue::Number(3.14);
 /// assert_eq!(v["foo"], JsonValue::Number(3.14));
 /// ```
 ///
-/// Like standard containers such as `Vec` or `HashMap`, it will panic when the given `JsonValue` value is not an object
+/// Like standard containers such as `Vec` or `BTreeMap`, it will panic when the given `JsonValue` value is not an object
 ///
 /// ```should_panic
 /// # use tinyjson::JsonValue;
@@ -518,8 +628,8 @@ impl Index<usize> for JsonValue {
 ///
 /// ```should_panic
 /// # use tinyjson::JsonValue;
-/// # use std::collections::HashMap;
-/// let mut v = JsonValue::from(HashMap::new());
+/// # use std::collections::BTreeMap;
+/// let mut v = JsonValue::from(BTreeMap::new());
 /// let _ = &mut v["foo"]; // Panic
 /// ```
 ///
@@ -546,8 +656,9 @@ impl<'a> IndexMut<&'a str> for JsonValue {
         let obj = match self {
             JsonValue::Object(o) => o,
             _ => panic!(
-                "Attempted to access to an object with key '{}' but actually it was {:?}",
-                key, self
+                "Attempted to access to an object with key '{}' but actually it was {}",
+                key,
+                self.summary()
             ),
         };
 
@@ -569,12 +680,12 @@ impl<'a> IndexMut<&'a str> for JsonValue {
 /// assert_eq!(b, &JsonValue::Boolean(true));
 /// ```
 ///
-/// Like standard containers such as `Vec` or `HashMap`, it will panic when the given `JsonValue` value is not an array
+/// Like standard containers such as `Vec` or `BTreeMap`, it will panic when the given `JsonValue` value is not an array
 ///
 /// ```should_panic
 /// # use tinyjson::JsonValue;
-/// use std::collections::HashMap;
-/// let mut v = JsonValue::from(HashMap::new());
+/// use std::collections::BTreeMap;
+/// let mut v = JsonValue::from(BTreeMap::new());
 /// let _ = &mut v[0]; // Panic
 /// ```
 ///
@@ -590,8 +701,9 @@ impl IndexMut<usize> for JsonValue {
         let array = match self {
             JsonValue::Array(a) => a,
             _ => panic!(
-                "Attempted to access to an array with index {} but actually the value was {:?}",
-                index, self,
+                "Attempted to access to an array with index {} but actually the value was {}",
+                index,
+                self.summary(),
             ),
         };
 
@@ -666,17 +778,17 @@ impl_from!(
     a: Vec<JsonValue> => Array(a)
 );
 impl_from!(
-    /// Convert `HashMap` value into `JsonValue`.
+    /// Convert `BTreeMap` value into `JsonValue`.
     ///
     /// ```
     /// use tinyjson::JsonValue;
-    /// use std::collections::HashMap;
-    /// let mut m = HashMap::new();
+    /// use std::collections::BTreeMap;
+    /// let mut m = BTreeMap::new();
     /// m.insert("foo".to_string(), 1.0.into());
     /// let v = JsonValue::from(m);
     /// assert!(v.is_object());
     /// ```
-    o: HashMap<String, JsonValue> => Object(o)
+    o: BTreeMap<String, JsonValue> => Object(o)
 );
 
 /// Error caused when trying to convert `JsonValue` into some wrong type value.
@@ -713,12 +825,14 @@ impl fmt::Display for UnexpectedValue {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "Unexpected JSON value: {:?}. Expected {} value",
-            self.value, self.expected
+            "Unexpected JSON value: {}. Expected {} value",
+            self.value.summary(),
+            self.expected
         )
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for UnexpectedValue {}
 
 /// Convert this error into the value which failed to be converted.
@@ -855,24 +969,24 @@ impl_try_from!(
     Vec<JsonValue>,
 );
 impl_try_from!(
-    /// Try to convert the `JsonValue` value into `HashMap<String, JsonValue>`. `UnexpectedValue` error happens when
+    /// Try to convert the `JsonValue` value into `BTreeMap<String, JsonValue>`. `UnexpectedValue` error happens when
     /// trying to convert an incorrect type value.
     ///
     /// ```
     /// use tinyjson::JsonValue;
     /// use std::convert::TryFrom;
-    /// use std::collections::HashMap;
+    /// use std::collections::BTreeMap;
     ///
-    /// let mut m = HashMap::new();
+    /// let mut m = BTreeMap::new();
     /// m.insert("foo".to_string(), 42.0.into());
     /// let v = JsonValue::from(m);
-    /// let r = <HashMap<_, _>>::try_from(v);
+    /// let r = <BTreeMap<_, _>>::try_from(v);
     /// assert!(r.is_ok());
     ///
     /// let v = JsonValue::from(1.0);
-    /// let r = <HashMap<_, _>>::try_from(v);
+    /// let r = <BTreeMap<_, _>>::try_from(v);
     /// assert!(r.is_err());
     /// ```
     JsonValue::Object(o) => o,
-    HashMap<String, JsonValue>,
+    BTreeMap<String, JsonValue>,
 );
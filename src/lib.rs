@@ -2,11 +2,11 @@
 //!
 //! Goals of this library are
 //!
-//! - **Simplicity**: This library uses standard containers like `Vec` or `HashMap` as its internal representation
+//! - **Simplicity**: This library uses standard containers like `Vec` or `BTreeMap` as its internal representation
 //!   and exposes it to users. Users can operate JSON values via the standard APIs. And it keeps this crate as small
 //!   as possible.
 //! - **Explicit**: This library does not hide memory allocation from users. You need to allocate memory like `Vec`,
-//!   `String`, `HashMap` by yourself. It is good for readers of your source code to show where memory allocations
+//!   `String`, `BTreeMap` by yourself. It is good for readers of your source code to show where memory allocations
 //!   happen. And you can have control of how memory is allocated (e.g. allocating memory in advance with
 //!   `with_capacity` method).
 //! - **No dependencies**: This library is built on top of only standard libraries.
@@ -20,7 +20,7 @@
 //!
 //! ```
 //! use tinyjson::JsonValue;
-//! use std::collections::HashMap;
+//! use std::collections::BTreeMap;
 //! use std::convert::TryInto;
 //!
 //! let s = r#"
@@ -39,8 +39,8 @@
 //! let parsed: JsonValue = s.parse().unwrap();
 //!
 //! // Access to inner value represented with standard containers
-//! let object: &HashMap<_, _> = parsed.get().unwrap();
-//! println!("Parsed HashMap: {:?}", object);
+//! let object: &BTreeMap<_, _> = parsed.get().unwrap();
+//! println!("Parsed BTreeMap: {:?}", object);
 //!
 //! // Generate JSON string
 //! println!("{}", parsed.stringify().unwrap());
@@ -52,11 +52,11 @@
 //! println!("Second element of \"arr\": {:?}", elem);
 //!
 //! // Convert to inner value represented with standard containers
-//! let object: HashMap<_, _> = parsed.try_into().unwrap();
-//! println!("Converted into HashMap: {:?}", object);
+//! let object: BTreeMap<_, _> = parsed.try_into().unwrap();
+//! println!("Converted into BTreeMap: {:?}", object);
 //!
 //! // Create JSON values from standard containers
-//! let mut m = HashMap::new();
+//! let mut m = BTreeMap::new();
 //! m.insert("foo".to_string(), true.into());
 //! let mut v = JsonValue::from(m);
 //!
@@ -68,30 +68,64 @@
 //!
 //! Any JSON value is represented with [`JsonValue`] enum. Each JSON types are mapped to Rust types as follows:
 //!
-//! | JSON    | Rust                         |
-//! |---------|------------------------------|
-//! | Number  | `f64`                        |
-//! | Boolean | `bool`                       |
-//! | String  | `String`                     |
-//! | Null    | `()`                         |
-//! | Array   | `Vec<JsonValue>`             |
-//! | Object  | `HashMap<String, JsonValue>` |
+//! | JSON    | Rust                          |
+//! |---------|-------------------------------|
+//! | Number  | `f64`                         |
+//! | Boolean | `bool`                        |
+//! | String  | `String`                      |
+//! | Null    | `()`                          |
+//! | Array   | `Vec<JsonValue>`              |
+//! | Object  | `BTreeMap<String, JsonValue>` |
 //!
 //! Flexible query APIs are available to access nested elements easily without panic. See [`JsonQuery`] and
 //! [`JsonQueryMut`] for more details.
+//!
+//! ## `no_std` support
+//!
+//! This library depends on `std` crate through `std` feature which is enabled by default. By disabling the feature, it
+//! can be used in `no_std` environment with only `alloc` crate. In the case,
+//!
+//! - APIs depending on `std::io` such as [`JsonValue::write_to`] are not available. Use [`FmtWriter`] to generate
+//!   JSON text with `core::fmt::Write` objects instead
+//!
+//! ```toml
+//! [dependencies]
+//! tinyjson = { version = "3", default-features = false }
+//! ```
 
+#![cfg_attr(not(feature = "std"), no_std)]
 // This library is built with Safe Rust
 #![forbid(unsafe_code)]
-// Suppress warning which prefers `matches!` macro to `match` statement since the macro was
-// introduced in recent Rust 1.42. This library should support older Rust.
+// Keep `match` statements written before `matches!` macro was available. The minimum supported Rust version is
+// declared as `rust-version` in Cargo.toml and checked on CI.
 #![allow(clippy::match_like_matches_macro)]
 
+extern crate alloc;
+
+mod encoding;
 mod generator;
+mod interned;
 mod json_value;
+#[cfg(feature = "std")]
+mod lines;
 mod parser;
 mod query;
+mod redaction;
+#[cfg(feature = "std")]
+mod stream;
+mod summary;
+mod writer;
 
+pub use encoding::{decode_bytes, parse_bytes, parse_bytes_strict, Encoding};
 pub use generator::*;
+pub use interned::{InternedJsonValue, KeyInterner};
 pub use json_value::{InnerAsRef, InnerAsRefMut, JsonValue, UnexpectedValue};
+#[cfg(feature = "std")]
+pub use lines::JsonLinesParser;
 pub use parser::*;
 pub use query::{ChildIndex, JsonQuery, JsonQueryMut};
+pub use redaction::Redaction;
+#[cfg(feature = "std")]
+pub use stream::JsonArrayStream;
+pub use summary::JsonSummary;
+pub use writer::JsonWriter;
@@ -0,0 +1,126 @@
+use crate::encoding::decode_utf8;
+use crate::parser::{JsonParseResult, JsonParser};
+use std::thread;
+
+fn is_blank(line: &[u8]) -> bool {
+    line.iter().all(|b| matches!(b, b' ' | b'\t' | b'\r'))
+}
+
+// Parse lines in the chunk. It returns the results with their 1-based line numbers in the chunk and the number of
+// newlines in the chunk
+fn parse_chunk(chunk: &[u8]) -> (Vec<(usize, JsonParseResult)>, usize) {
+    let mut results = vec![];
+    let mut newlines = 0;
+    for (idx, line) in chunk.split(|b| *b == b'\n').enumerate() {
+        newlines = idx;
+        if is_blank(line) {
+            continue;
+        }
+        let parsed = decode_utf8(line).and_then(|s| JsonParser::new(s.chars()).parse());
+        results.push((idx + 1, parsed.map_err(|err| err.offset_line(idx))));
+    }
+    (results, newlines)
+}
+
+// Split the input into the given number of chunks at newlines. Each chunk has roughly the same size
+fn split_chunks(input: &[u8], count: usize) -> Vec<&[u8]> {
+    let size = input.len() / count + 1;
+    let mut chunks = Vec::with_capacity(count);
+    let mut rest = input;
+    while !rest.is_empty() {
+        let end = if rest.len() <= size {
+            rest.len()
+        } else {
+            match rest[size..].iter().position(|b| *b == b'\n') {
+                Some(idx) => size + idx + 1,
+                None => rest.len(),
+            }
+        };
+        let (chunk, next) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = next;
+    }
+    chunks
+}
+
+/// Parser for [JSON Lines](https://jsonlines.org/) (also known as NDJSON) text which parses lines on multiple threads.
+///
+/// The input is split into chunks at newlines and each chunk is parsed by [`JsonParser`] on a separate thread. Each
+/// line must be a JSON value encoded in UTF-8. Blank lines are ignored. Each result is paired with the 1-based line
+/// number of the line in the input.
+///
+/// ```
+/// use tinyjson::{JsonLinesParser, JsonValue};
+///
+/// let input = b"{\"id\": 1}\n{\"id\": 2}\n\n[1, 2\n";
+/// let results = JsonLinesParser::new().threads(2).parse(input);
+///
+/// // Results are in the same order as the input lines
+/// assert_eq!(results.len(), 3);
+/// let (line, result) = &results[0];
+/// assert_eq!(*line, 1);
+/// assert_eq!(result.as_ref().unwrap()["id"], JsonValue::from(1.0));
+/// let (line, result) = &results[1];
+/// assert_eq!(*line, 2);
+/// assert_eq!(result.as_ref().unwrap()["id"], JsonValue::from(2.0));
+///
+/// // Line numbers are counted from the start of the input
+/// let (line, result) = &results[2];
+/// assert_eq!(*line, 4);
+/// assert_eq!(result.as_ref().unwrap_err().line(), 4);
+/// ```
+#[derive(Debug, Clone)]
+pub struct JsonLinesParser {
+    threads: usize,
+}
+
+impl Default for JsonLinesParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonLinesParser {
+    /// Create a new `JsonLinesParser` instance. By default, the number of threads is the available parallelism of
+    /// the system.
+    pub fn new() -> Self {
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        Self { threads }
+    }
+
+    /// Set the number of threads to parse the input. When `1` or `0` is given, the input is parsed on the current
+    /// thread without spawning any thread.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Parse the given JSON Lines text. The results of non-blank lines are returned with their line numbers in the
+    /// same order as the input. Line numbers of results and parse errors are counted from the start of the input.
+    pub fn parse(&self, input: &[u8]) -> Vec<(usize, JsonParseResult)> {
+        let chunks = split_chunks(input, self.threads);
+        if chunks.len() <= 1 {
+            return parse_chunk(input).0;
+        }
+
+        let parsed: Vec<_> = thread::scope(|s| {
+            let handles: Vec<_> = chunks
+                .into_iter()
+                .map(|chunk| s.spawn(move || parse_chunk(chunk)))
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        let mut all = Vec::with_capacity(parsed.iter().map(|(r, _)| r.len()).sum());
+        let mut offset = 0;
+        for (results, newlines) in parsed {
+            all.extend(
+                results
+                    .into_iter()
+                    .map(|(line, r)| (line + offset, r.map_err(|e| e.offset_line(offset)))),
+            );
+            offset += newlines;
+        }
+        all
+    }
+}
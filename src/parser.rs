@@ -1,10 +1,33 @@
-use std::char;
-use std::collections::HashMap;
-use std::fmt;
-use std::iter::Peekable;
-use std::str::FromStr;
-
-use crate::JsonValue;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::Peekable;
+use core::mem;
+use core::str::FromStr;
+
+use crate::{InnerAsRefMut, JsonValue};
+
+/// Kind of parse error. See [`JsonParseError::kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum JsonParseErrorKind {
+    /// The input is not a valid JSON text.
+    Syntax,
+    /// The input byte sequence is not correctly encoded. See [`crate::parse_bytes`].
+    Encoding,
+    /// The number literal overflows to infinity. This is reported only when [`JsonParser::strict_numbers`] is enabled.
+    NumberOverflow,
+    /// The non-zero number literal underflows to zero. This is reported only when [`JsonParser::strict_numbers`] is
+    /// enabled.
+    NumberUnderflow,
+    /// The integer literal cannot be represented exactly with `f64`. This is reported only when
+    /// [`JsonParser::strict_numbers`] is enabled.
+    NumberPrecisionLoss,
+    /// Reading the input failed. This is reported only by [`crate::JsonArrayStream`].
+    Io,
+}
 
 /// Parse error.
 ///
@@ -15,14 +38,36 @@ use crate::JsonValue;
 /// ```
 #[derive(Debug)]
 pub struct JsonParseError {
+    kind: JsonParseErrorKind,
     msg: String,
     line: usize,
     col: usize,
 }
 
 impl JsonParseError {
-    fn new(msg: String, line: usize, col: usize) -> JsonParseError {
-        JsonParseError { msg, line, col }
+    pub(crate) fn new(msg: String, line: usize, col: usize) -> JsonParseError {
+        Self::with_kind(JsonParseErrorKind::Syntax, msg, line, col)
+    }
+
+    pub(crate) fn with_kind(
+        kind: JsonParseErrorKind,
+        msg: String,
+        line: usize,
+        col: usize,
+    ) -> JsonParseError {
+        JsonParseError {
+            kind,
+            msg,
+            line,
+            col,
+        }
+    }
+
+    // Move the position of the error forward by the given number of lines
+    #[cfg(feature = "std")]
+    pub(crate) fn offset_line(mut self, lines: usize) -> JsonParseError {
+        self.line += lines;
+        self
     }
 
     /// Get the line numbr where the parse error happened. This value is 1-based.
@@ -46,6 +91,17 @@ impl JsonParseError {
     pub fn column(&self) -> usize {
         self.col
     }
+
+    /// Get the kind of the parse error.
+    ///
+    /// ```
+    /// use tinyjson::{JsonParser, JsonParseErrorKind};
+    /// let error = JsonParser::new("[1, 2, 3".chars()).parse().unwrap_err();
+    /// assert_eq!(error.kind(), JsonParseErrorKind::Syntax);
+    /// ```
+    pub fn kind(&self) -> JsonParseErrorKind {
+        self.kind
+    }
 }
 
 impl fmt::Display for JsonParseError {
@@ -58,6 +114,7 @@ impl fmt::Display for JsonParseError {
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for JsonParseError {}
 
 /// Convenient type alias for parse results.
@@ -73,6 +130,143 @@ fn is_whitespace(c: char) -> bool {
     }
 }
 
+// Check the number is exactly the value of the number literal when the literal represents an integer such as `123`,
+// `1.5e3` or `100.0`. Other literals always pass the check.
+fn is_exact_integer(lit: &str, n: f64) -> bool {
+    let lit = lit.trim_start_matches('-');
+    let (mantissa, exp) = match lit.find(['e', 'E']) {
+        Some(idx) => {
+            let exp = &lit[idx + 1..];
+            let saturated = if exp.starts_with('-') {
+                i64::MIN
+            } else {
+                i64::MAX
+            };
+            (&lit[..idx], exp.parse().unwrap_or(saturated))
+        }
+        None => (lit, 0),
+    };
+    let (int, frac) = match mantissa.find('.') {
+        Some(idx) => (&mantissa[..idx], &mantissa[idx + 1..]),
+        None => (mantissa, ""),
+    };
+
+    // The value is `int` and `frac` digits followed by `zeros` zeros. Trailing zeros of the digits are moved into
+    // `zeros` so that literals like `12300e-2` are checked as integers
+    let frac = frac.trim_end_matches('0');
+    let mut zeros = exp.saturating_sub(frac.len() as i64);
+    let mut int = int;
+    if frac.is_empty() {
+        let trimmed = int.trim_end_matches('0');
+        zeros = zeros.saturating_add((int.len() - trimmed.len()) as i64);
+        int = trimmed;
+    }
+    if zeros < 0 {
+        return true; // Not an integer since non-zero digits remain after the decimal point
+    }
+    let int = int.trim_start_matches('0');
+    let frac = if int.is_empty() {
+        frac.trim_start_matches('0')
+    } else {
+        frac
+    };
+    let len = int.len() + frac.len();
+    // Integers less than 10^15 are always representable since they are smaller than 2^53
+    if len == 0 || len as i64 + zeros <= 15 {
+        return true;
+    }
+
+    // Format with precision 0 to get the exact integer value of `n`
+    let exact = format!("{:.0}", n.abs());
+    exact.len() as i64 == len as i64 + zeros
+        && exact[..int.len()] == *int
+        && exact[int.len()..len] == *frac
+        && exact[len..].bytes().all(|b| b == b'0')
+}
+
+fn digit_value(d: char) -> i64 {
+    d as i64 - '0' as i64
+}
+
+// Powers of 10 which can be represented exactly with `f64`
+const POW10: [f64; 23] = [
+    1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10, 1e11, 1e12, 1e13, 1e14, 1e15, 1e16,
+    1e17, 1e18, 1e19, 1e20, 1e21, 1e22,
+];
+
+// Decimal digits of number literal accumulated while parsing. The value is `mantissa * 10^exp`
+#[derive(Default)]
+struct Digits {
+    mantissa: u64,
+    exp: i64,
+    len: usize,      // Number of significant digits in `mantissa`
+    truncated: bool, // Some significant digits did not fit in `mantissa`
+}
+
+impl Digits {
+    fn push(&mut self, d: char) {
+        if self.len == 19 {
+            // More than 19 digits may overflow `u64`. Such literal is parsed by the slow path
+            self.truncated = true;
+            return;
+        }
+        self.mantissa = self.mantissa * 10 + digit_value(d) as u64;
+        if self.mantissa != 0 {
+            self.len += 1;
+        }
+    }
+
+    fn push_fraction(&mut self, d: char) {
+        self.push(d);
+        self.exp -= 1;
+    }
+
+    // Convert the digits into `f64` without rounding error when both the mantissa and the power of 10 are exactly
+    // representable with `f64`. Since IEEE 754 guarantees that a single multiplication or division is correctly
+    // rounded, the result is identical to `str::parse`. This is known as Clinger's fast path.
+    fn fast_path(&self) -> Option<f64> {
+        const MAX_MANTISSA: u64 = 1 << 53;
+
+        if self.truncated {
+            return None;
+        }
+        if self.mantissa == 0 {
+            return Some(0.0);
+        }
+        if self.mantissa > MAX_MANTISSA {
+            return None;
+        }
+
+        let m = self.mantissa as f64;
+        match self.exp {
+            0..=22 => Some(m * POW10[self.exp as usize]),
+            -22..=-1 => Some(m / POW10[-self.exp as usize]),
+            23..=37 => {
+                // Move the extra zeros to the mantissa (e.g. 12e25 = 12000e22) while it is still exact
+                let m = self.mantissa.checked_mul(10u64.pow(self.exp as u32 - 22))?;
+                if m > MAX_MANTISSA {
+                    return None;
+                }
+                Some(m as f64 * POW10[22])
+            }
+            _ => None,
+        }
+    }
+}
+
+// Get the inner value of the given `JsonValue` value to reuse its allocation. When the type is different, the value is
+// replaced with the default value of the type.
+fn reuse<T>(v: &mut JsonValue) -> &mut T
+where
+    T: InnerAsRefMut + Default,
+    JsonValue: From<T>,
+{
+    if v.get_mut::<T>().is_none() {
+        *v = T::default().into();
+    }
+    v.get_mut().unwrap()
+}
+
 /// JSON parser to parse UTF-8 string into `JsonValue` value.
 ///
 /// Basically you don't need to use this struct directly thanks to `FromStr` trait implementation.
@@ -93,6 +287,11 @@ where
     chars: Peekable<I>,
     line: usize,
     col: usize,
+    key: String,                     // Buffer for parsing keys of existing objects
+    stash: Vec<(String, JsonValue)>, // Entries of objects being parsed. See `parse_object`
+    num: String,                     // Buffer for parsing number literals
+    strict_numbers: bool,
+    allow_non_finite: bool,
 }
 
 impl<I: Iterator<Item = char>> JsonParser<I> {
@@ -103,13 +302,88 @@ impl<I: Iterator<Item = char>> JsonParser<I> {
             chars: it.peekable(),
             line: 1,
             col: 0,
+            key: String::new(),
+            stash: Vec::new(),
+            num: String::new(),
+            strict_numbers: false,
+            allow_non_finite: false,
         }
     }
 
-    fn err<T>(&self, msg: String) -> Result<T, JsonParseError> {
+    /// Make the parser fail when a number literal cannot be represented with `f64` without altering its value. By
+    /// default, number literals are silently rounded to the nearest `f64` values. When this option is enabled, the
+    /// following number literals cause a parse error with the corresponding [`JsonParseErrorKind`].
+    ///
+    /// - [`JsonParseErrorKind::NumberOverflow`]: The literal overflows to infinity (e.g. `1e400`)
+    /// - [`JsonParseErrorKind::NumberUnderflow`]: The non-zero literal underflows to zero (e.g. `1e-400`)
+    /// - [`JsonParseErrorKind::NumberPrecisionLoss`]: The literal of an integer value cannot be represented exactly
+    ///   (e.g. `12345678901234567890`, `1.2345678901234567890e19` or `12345678901234567890.0`)
+    ///
+    /// Note that fractional number literals like `0.1` are not checked since they usually cannot be represented
+    /// exactly with binary floating point numbers. On the other hand, large numbers in exponent notation are integers
+    /// and checked. For example, `1e23` is an error since the nearest `f64` value is `99999999999999991611392`.
+    ///
+    /// ```
+    /// use tinyjson::{JsonParser, JsonParseErrorKind};
+    ///
+    /// let error = JsonParser::new("12345678901234567890".chars())
+    ///     .strict_numbers(true)
+    ///     .parse()
+    ///     .unwrap_err();
+    /// assert_eq!(error.kind(), JsonParseErrorKind::NumberPrecisionLoss);
+    ///
+    /// let error = JsonParser::new("[1e400]".chars())
+    ///     .strict_numbers(true)
+    ///     .parse()
+    ///     .unwrap_err();
+    /// assert_eq!(error.kind(), JsonParseErrorKind::NumberOverflow);
+    ///
+    /// // 2^64 can be represented exactly
+    /// let value = JsonParser::new("18446744073709551616".chars())
+    ///     .strict_numbers(true)
+    ///     .parse()
+    ///     .unwrap();
+    /// assert_eq!(value, 18446744073709551616.0.into());
+    /// ```
+    pub fn strict_numbers(mut self, enabled: bool) -> Self {
+        self.strict_numbers = enabled;
+        self
+    }
+
+    /// Accept `NaN`, `Infinity` and `-Infinity` literals as numbers like JSON5. They are not allowed in JSON but are
+    /// sometimes written by other libraries. See also [`crate::NonFinitePolicy::WriteLiteral`].
+    ///
+    /// ```
+    /// use tinyjson::{JsonParser, JsonValue};
+    ///
+    /// assert!(JsonParser::new("[NaN]".chars()).parse().is_err());
+    ///
+    /// let v = JsonParser::new("[NaN, Infinity, -Infinity]".chars())
+    ///     .allow_non_finite(true)
+    ///     .parse()
+    ///     .unwrap();
+    /// let a: &Vec<_> = v.get().unwrap();
+    /// assert!(a[0].get::<f64>().unwrap().is_nan());
+    /// assert_eq!(a[1], JsonValue::from(f64::INFINITY));
+    /// assert_eq!(a[2], JsonValue::from(f64::NEG_INFINITY));
+    /// ```
+    pub fn allow_non_finite(mut self, enabled: bool) -> Self {
+        self.allow_non_finite = enabled;
+        self
+    }
+
+    pub(crate) fn err<T>(&self, msg: String) -> Result<T, JsonParseError> {
         Err(JsonParseError::new(msg, self.line, self.col))
     }
 
+    pub(crate) fn err_with_kind<T>(
+        &self,
+        kind: JsonParseErrorKind,
+        msg: String,
+    ) -> Result<T, JsonParseError> {
+        Err(JsonParseError::with_kind(kind, msg, self.line, self.col))
+    }
+
     fn unexpected_eof(&self) -> Result<char, JsonParseError> {
         Err(JsonParseError::new(
             String::from("Unexpected EOF"),
@@ -127,7 +401,7 @@ impl<I: Iterator<Item = char>> JsonParser<I> {
         }
     }
 
-    fn peek(&mut self) -> Result<char, JsonParseError> {
+    pub(crate) fn peek(&mut self) -> Result<char, JsonParseError> {
         while let Some(c) = self.chars.peek().copied() {
             if !is_whitespace(c) {
                 return Ok(c);
@@ -138,7 +412,7 @@ impl<I: Iterator<Item = char>> JsonParser<I> {
         self.unexpected_eof()
     }
 
-    fn next(&mut self) -> Option<char> {
+    pub(crate) fn next(&mut self) -> Option<char> {
         while let Some(c) = self.chars.next() {
             self.next_pos(c);
             if !is_whitespace(c) {
@@ -148,7 +422,7 @@ impl<I: Iterator<Item = char>> JsonParser<I> {
         None
     }
 
-    fn consume(&mut self) -> Result<char, JsonParseError> {
+    pub(crate) fn consume(&mut self) -> Result<char, JsonParseError> {
         if let Some(c) = self.next() {
             Ok(c)
         } else {
@@ -165,61 +439,115 @@ impl<I: Iterator<Item = char>> JsonParser<I> {
         }
     }
 
-    fn parse_object(&mut self) -> JsonParseResult {
+    // Parse a key of object and the following ':'
+    pub(crate) fn parse_key(&mut self, key: &mut String) -> Result<(), JsonParseError> {
+        if self.peek()? != '"' {
+            let v = self.parse_any()?;
+            return self.err(format!("Key of object must be string but found {:?}", v));
+        }
+        self.parse_str(key)?;
+
+        let c = self.consume()?;
+        if c != ':' {
+            return self.err(format!(
+                "':' is expected after key of object but actually found '{}'",
+                c
+            ));
+        }
+        Ok(())
+    }
+
+    // Parse the delimiter after an element of object. Returns `true` when the next element follows
+    pub(crate) fn parse_object_delim(&mut self) -> Result<bool, JsonParseError> {
+        match self.consume()? {
+            ',' => Ok(true),
+            '}' => Ok(false),
+            c => self.err(format!(
+                "',' or '}}' is expected for object but actually found '{}'",
+                c.escape_debug(),
+            )),
+        }
+    }
+
+    fn parse_object(&mut self, value: &mut JsonValue) -> Result<(), JsonParseError> {
         if self.consume()? != '{' {
             return self.err(String::from("Object must starts with '{'"));
         }
 
+        let m = reuse::<BTreeMap<_, _>>(value);
+
         if self.peek()? == '}' {
             self.consume().unwrap();
-            return Ok(JsonValue::Object(HashMap::new()));
+            m.clear();
+            return Ok(());
+        }
+
+        if m.is_empty() {
+            loop {
+                let mut key = String::new();
+                self.parse_key(&mut key)?;
+                m.insert(key, self.parse_any()?);
+                if !self.parse_object_delim()? {
+                    return Ok(());
+                }
+            }
         }
 
-        let mut m = HashMap::new();
+        // Entries of the existing object are moved to the stash while parsing to reuse their keys and values. After
+        // parsing the object, the entries which did not appear in the input are removed and the stashed entries are
+        // moved back to the object. Duplicate keys are resolved when moving the entries back so that the last value
+        // wins.
+        let start = self.stash.len();
         loop {
-            let key = match self.parse_any()? {
-                JsonValue::String(s) => s,
-                v => return self.err(format!("Key of object must be string but found {:?}", v)),
+            let mut key = mem::take(&mut self.key);
+            self.parse_key(&mut key)?;
+            let (key, mut elem) = match m.remove_entry(key.as_str()) {
+                Some(entry) => {
+                    self.key = key;
+                    entry
+                }
+                None => (key, JsonValue::Null),
             };
 
-            let c = self.consume()?;
-            if c != ':' {
-                return self.err(format!(
-                    "':' is expected after key of object but actually found '{}'",
-                    c
-                ));
-            }
+            self.parse_any_into(&mut elem)?;
+            self.stash.push((key, elem));
 
-            m.insert(key, self.parse_any()?);
-
-            match self.consume()? {
-                ',' => {}
-                '}' => return Ok(JsonValue::Object(m)),
-                c => {
-                    return self.err(format!(
-                        "',' or '}}' is expected for object but actually found '{}'",
-                        c.escape_debug(),
-                    ))
-                }
+            if !self.parse_object_delim()? {
+                break;
             }
         }
+
+        m.clear();
+        m.extend(self.stash.drain(start..));
+        Ok(())
     }
 
-    fn parse_array(&mut self) -> JsonParseResult {
+    fn parse_array(&mut self, value: &mut JsonValue) -> Result<(), JsonParseError> {
         if self.consume()? != '[' {
             return self.err(String::from("Array must starts with '['"));
         }
 
+        let v = reuse::<Vec<_>>(value);
+
         if self.peek()? == ']' {
             self.consume().unwrap();
-            return Ok(JsonValue::Array(vec![]));
+            v.clear();
+            return Ok(());
         }
 
-        let mut v = vec![self.parse_any()?];
+        let mut len = 0;
         loop {
+            // Reuse the existing element if exists
+            if let Some(elem) = v.get_mut(len) {
+                self.parse_any_into(elem)?;
+            } else {
+                v.push(self.parse_any()?);
+            }
+            len += 1;
+
             match self.consume()? {
                 ',' => {}
-                ']' => return Ok(JsonValue::Array(v)),
+                ']' => break,
                 c => {
                     return self.err(format!(
                         "',' or ']' is expected for array but actually found '{}'",
@@ -227,9 +555,10 @@ impl<I: Iterator<Item = char>> JsonParser<I> {
                     ))
                 }
             }
-
-            v.push(self.parse_any()?); // Next element
         }
+
+        v.truncate(len);
+        Ok(())
     }
 
     fn push_utf16(&self, s: &mut String, utf16: &mut Vec<u16>) -> Result<(), JsonParseError> {
@@ -245,13 +574,13 @@ impl<I: Iterator<Item = char>> JsonParser<I> {
         Ok(())
     }
 
-    fn parse_string(&mut self) -> JsonParseResult {
+    fn parse_str(&mut self, s: &mut String) -> Result<(), JsonParseError> {
         if self.consume()? != '"' {
             return self.err(String::from("String must starts with double quote"));
         }
 
+        s.clear();
         let mut utf16 = Vec::new(); // Buffer for parsing \uXXXX UTF-16 characters
-        let mut s = String::new();
         loop {
             let c = match self.consume_no_skip()? {
                 '\\' => match self.consume_no_skip()? {
@@ -281,10 +610,7 @@ impl<I: Iterator<Item = char>> JsonParser<I> {
                     }
                     c => return self.err(format!("'\\{}' is invalid escaped character", c)),
                 },
-                '"' => {
-                    self.push_utf16(&mut s, &mut utf16)?;
-                    return Ok(JsonValue::String(s));
-                }
+                '"' => return self.push_utf16(s, &mut utf16),
                 // Note: c.is_control() is not available here because JSON accepts 0x7f (DEL) in
                 // string literals but 0x7f is control character.
                 // Rough spec of JSON says string literal cannot contain control characters. But it
@@ -298,12 +624,16 @@ impl<I: Iterator<Item = char>> JsonParser<I> {
                 c => c,
             };
 
-            self.push_utf16(&mut s, &mut utf16)?;
+            self.push_utf16(s, &mut utf16)?;
 
             s.push(c);
         }
     }
 
+    fn parse_string(&mut self, value: &mut JsonValue) -> Result<(), JsonParseError> {
+        self.parse_str(reuse(value))
+    }
+
     fn parse_constant(&mut self, s: &'static str) -> Option<JsonParseError> {
         for c in s.chars() {
             match self.consume_no_skip() {
@@ -342,20 +672,53 @@ impl<I: Iterator<Item = char>> JsonParser<I> {
         }
     }
 
+    fn parse_non_finite(&mut self) -> Result<f64, JsonParseError> {
+        let (lit, n) = if let Some('N') = self.chars.peek() {
+            ("NaN", f64::NAN)
+        } else {
+            ("Infinity", f64::INFINITY)
+        };
+        match self.parse_constant(lit) {
+            Some(err) => Err(err),
+            None => Ok(n),
+        }
+    }
+
     fn parse_number(&mut self) -> JsonParseResult {
-        let mut s = String::new();
+        // Reuse the buffer of number literal to avoid allocating a new string for each number
+        let mut lit = mem::take(&mut self.num);
+        lit.clear();
+        let n = self.parse_number_lit(&mut lit)?;
+        self.num = lit;
+        Ok(JsonValue::Number(n))
+    }
+
+    fn parse_number_lit(&mut self, s: &mut String) -> Result<f64, JsonParseError> {
+        let mut digits = Digits::default();
 
-        if let Some('-') = self.chars.peek() {
+        let neg = if let Some('-') = self.chars.peek() {
             self.consume_no_skip().unwrap();
             s.push('-');
+            true
+        } else {
+            false
         };
 
+        if self.allow_non_finite && neg {
+            if let Some('N' | 'I') = self.chars.peek() {
+                return Ok(-self.parse_non_finite()?);
+            }
+        }
+
         match self.consume_no_skip()? {
             '0' => s.push('0'),
             d @ '1'..='9' => {
                 s.push(d);
-                while let Some('0'..='9') = self.chars.peek() {
-                    s.push(self.consume_no_skip().unwrap());
+                digits.push(d);
+                while let Some(d @ '0'..='9') = self.chars.peek().copied() {
+                    self.consume_no_skip().unwrap();
+                    s.push(d);
+                    digits.push(d);
                 }
             }
             c => {
@@ -368,27 +731,40 @@ impl<I: Iterator<Item = char>> JsonParser<I> {
             s.push(self.consume_no_skip().unwrap()); // Eat '.'
 
             match self.consume_no_skip()? {
-                d @ '0'..='9' => s.push(d),
+                d @ '0'..='9' => {
+                    s.push(d);
+                    digits.push_fraction(d);
+                }
                 c => {
                     let msg = format!("At least one digit must follow after '.' but got {}", c);
                     return self.err(msg);
                 }
             }
 
-            while let Some('0'..='9') = self.chars.peek() {
-                s.push(self.consume_no_skip().unwrap());
+            while let Some(d @ '0'..='9') = self.chars.peek().copied() {
+                self.consume_no_skip().unwrap();
+                s.push(d);
+                digits.push_fraction(d);
             }
         }
 
         if let Some('e' | 'E') = self.chars.peek() {
             s.push(self.consume_no_skip().unwrap()); // Eat 'e' or 'E'
 
-            if let Some('-' | '+') = self.chars.peek() {
-                s.push(self.consume_no_skip().unwrap());
-            }
+            let neg_exp = match self.chars.peek().copied() {
+                Some(c @ ('-' | '+')) => {
+                    self.consume_no_skip().unwrap();
+                    s.push(c);
+                    c == '-'
+                }
+                _ => false,
+            };
 
-            match self.consume_no_skip()? {
-                d @ '0'..='9' => s.push(d),
+            let mut exp: i64 = match self.consume_no_skip()? {
+                d @ '0'..='9' => {
+                    s.push(d);
+                    digit_value(d)
+                }
                 c => {
                     return self.err(format!(
                         "At least one digit must follow exponent part of number but got {}",
@@ -397,33 +773,104 @@ impl<I: Iterator<Item = char>> JsonParser<I> {
                 }
             };
 
-            while let Some('0'..='9') = self.chars.peek() {
-                s.push(self.consume_no_skip().unwrap());
+            while let Some(d @ '0'..='9') = self.chars.peek().copied() {
+                self.consume_no_skip().unwrap();
+                s.push(d);
+                // Saturate huge exponents. They never take the fast path
+                exp = exp.saturating_mul(10).saturating_add(digit_value(d));
             }
+
+            digits.exp = digits.exp.saturating_add(if neg_exp { -exp } else { exp });
         }
 
-        match s.parse() {
-            Ok(n) => Ok(JsonValue::Number(n)),
-            Err(err) => self.err(format!("Invalid number literal '{}': {}", s, err)),
+        let n = match digits.fast_path() {
+            Some(n) if neg => -n,
+            Some(n) => n,
+            None => match s.parse() {
+                Ok(n) => n,
+                Err(err) => return self.err(format!("Invalid number literal '{}': {}", s, err)),
+            },
+        };
+
+        if self.strict_numbers {
+            self.check_number(s, n)?;
         }
+
+        Ok(n)
     }
 
-    fn parse_any(&mut self) -> JsonParseResult {
-        match self.peek()? {
-            '0'..='9' | '-' => self.parse_number(),
-            '"' => self.parse_string(),
-            '[' => self.parse_array(),
-            '{' => self.parse_object(),
-            't' => self.parse_true(),
-            'f' => self.parse_false(),
-            'n' => self.parse_null(),
-            c => self.err(format!("Invalid character: {}", c.escape_debug())),
-        }
+    // Check the number literal is not altered when converting it into `f64` value
+    fn check_number(&self, lit: &str, n: f64) -> Result<(), JsonParseError> {
+        use JsonParseErrorKind::*;
+
+        let (kind, msg) = if n.is_infinite() {
+            (NumberOverflow, "overflows to infinity")
+        } else if n == 0.0
+            && lit
+                .bytes()
+                .take_while(|b| !matches!(b, b'e' | b'E'))
+                .any(|b| matches!(b, b'1'..=b'9'))
+        {
+            (NumberUnderflow, "underflows to zero")
+        } else if !is_exact_integer(lit, n) {
+            (NumberPrecisionLoss, "cannot be represented exactly")
+        } else {
+            return Ok(());
+        };
+
+        let msg = format!(
+            "Number literal '{}' {} as 64-bit floating point number",
+            lit, msg
+        );
+        self.err_with_kind(kind, msg)
+    }
+
+    fn parse_any_into(&mut self, value: &mut JsonValue) -> Result<(), JsonParseError> {
+        *value = match self.peek()? {
+            '0'..='9' | '-' => self.parse_number()?,
+            '"' => return self.parse_string(value),
+            '[' => return self.parse_array(value),
+            '{' => return self.parse_object(value),
+            't' => self.parse_true()?,
+            'f' => self.parse_false()?,
+            'n' => self.parse_null()?,
+            'N' | 'I' if self.allow_non_finite => JsonValue::Number(self.parse_non_finite()?),
+            c => return self.err(format!("Invalid character: {}", c.escape_debug())),
+        };
+        Ok(())
+    }
+
+    pub(crate) fn parse_any(&mut self) -> JsonParseResult {
+        let mut v = JsonValue::Null;
+        self.parse_any_into(&mut v)?;
+        Ok(v)
     }
 
     /// Run the parser to parse one JSON value.
     pub fn parse(&mut self) -> JsonParseResult {
-        let v = self.parse_any()?;
+        let mut v = JsonValue::Null;
+        self.parse_into(&mut v)?;
+        Ok(v)
+    }
+
+    /// Run the parser to parse one JSON value and store it in the given `JsonValue` value. Unlike
+    /// [`JsonParser::parse`], this method reuses the memory allocations of the given value where the shape of the
+    /// parsed value matches: `String` values, elements of `Vec` values, and keys and values of `BTreeMap` values. This
+    /// is useful when parsing many JSON documents with the same shape repeatedly.
+    ///
+    /// When the parse fails, the given value is partially updated and its content is unspecified.
+    ///
+    /// ```
+    /// use tinyjson::{JsonParser, JsonValue};
+    ///
+    /// let mut value = JsonValue::Null;
+    /// for msg in [r#"{"id": 1, "tags": ["a"]}"#, r#"{"id": 2, "tags": ["b", "c"]}"#] {
+    ///     JsonParser::new(msg.chars()).parse_into(&mut value).unwrap();
+    /// }
+    /// assert_eq!(value, r#"{"id": 2, "tags": ["b", "c"]}"#.parse().unwrap());
+    /// ```
+    pub fn parse_into(&mut self, value: &mut JsonValue) -> Result<(), JsonParseError> {
+        self.parse_any_into(value)?;
 
         if let Some(c) = self.next() {
             return self.err(format!(
@@ -432,7 +879,7 @@ impl<I: Iterator<Item = char>> JsonParser<I> {
             ));
         }
 
-        Ok(v)
+        Ok(())
     }
 }
 
@@ -8,7 +8,7 @@ use crate::json_value::{InnerAsRef, InnerAsRefMut, JsonValue};
 /// - `.child_by(|value| ...)`: Access by value predicate
 ///
 /// [`JsonValue`] also supports to access nested elements with `[]` operators, but they panics when the element does
-/// not exist like `Vec` or `HashMap`.
+/// not exist like `Vec` or `BTreeMap`.
 /// ```
 /// use tinyjson::JsonValue;
 ///
@@ -139,7 +139,7 @@ impl<'val> JsonQuery<'val> {
 /// - `.child_by(|value| ...)`: Access by value predicate
 ///
 /// [`JsonValue`] also supports to access nested elements with `[]` operators, but they panics when the element does
-/// not exist like `Vec` or `HashMap`.
+/// not exist like `Vec` or `BTreeMap`.
 ///
 /// Unlike [`JsonQuery`], methods of this type moves `self` since Rust does not allow to copy mutable references.
 /// ```
@@ -328,6 +328,8 @@ pub trait ChildIndex {
     fn index_mut(self, v: JsonQueryMut<'_>) -> JsonQueryMut<'_>;
 }
 
+// Explicit lifetime is kept as it was. Recent clippy suggests eliding it
+#[allow(clippy::needless_lifetimes)]
 impl<'key> ChildIndex for &'key str {
     fn index<'a>(self, v: &JsonQuery<'a>) -> JsonQuery<'a> {
         let inner = if let Some(JsonValue::Object(obj)) = v.0 {
@@ -0,0 +1,240 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+// Element of a path pattern such as `.password`, `.*`, `[0]` or `[*]`
+#[derive(Debug)]
+enum PathPattern<'a> {
+    Key(&'a str), // Glob pattern
+    Index(usize),
+    AnyIndex,
+}
+
+// Element of the path to the value being written
+pub(crate) enum Segment<'k> {
+    Key(&'k str),
+    Index(usize),
+}
+
+// Match the string with the glob pattern case-insensitively. `*` matches any sequence of characters and `?` matches
+// any single character.
+fn glob_match(pattern: &str, s: &str) -> bool {
+    fn eq(l: char, r: char) -> bool {
+        l == r || l.to_lowercase().eq(r.to_lowercase())
+    }
+
+    // Byte offsets in the pattern and the string
+    let (mut p, mut i) = (0, 0);
+    // Offsets just after the last `*` and where the `*` stops consuming the string
+    let mut backtrack = None;
+    while let Some(c) = s[i..].chars().next() {
+        match pattern[p..].chars().next() {
+            Some('*') => {
+                p += 1;
+                backtrack = Some((p, i));
+            }
+            Some(pc) if pc == '?' || eq(pc, c) => {
+                p += pc.len_utf8();
+                i += c.len_utf8();
+            }
+            _ => match backtrack {
+                Some((bp, bi)) => {
+                    // Let the last `*` consume one more character
+                    let bi = bi + s[bi..].chars().next().map_or(0, char::len_utf8);
+                    backtrack = Some((bp, bi));
+                    p = bp;
+                    i = bi;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].chars().all(|c| c == '*')
+}
+
+fn parse_path(path: &str) -> Vec<PathPattern<'_>> {
+    let mut patterns = Vec::new();
+    let mut rest = path;
+    while !rest.is_empty() {
+        if let Some(r) = rest.strip_prefix('[') {
+            let (inner, r) = match r.find(']') {
+                Some(idx) => (&r[..idx], &r[idx + 1..]),
+                None => (r, ""),
+            };
+            patterns.push(match inner {
+                "*" => PathPattern::AnyIndex,
+                _ => match inner.parse() {
+                    Ok(idx) => PathPattern::Index(idx),
+                    Err(_) => PathPattern::Key(inner),
+                },
+            });
+            rest = r;
+        } else {
+            let r = rest.strip_prefix('.').unwrap_or(rest);
+            let end = r.find(['.', '[']).unwrap_or(r.len());
+            patterns.push(PathPattern::Key(&r[..end]));
+            rest = &r[end..];
+        }
+    }
+    patterns
+}
+
+/// Rules to hide sensitive values such as passwords and tokens in the output of [`crate::JsonGenerator`]. Set it via
+/// [`crate::JsonGenerator::redact`].
+///
+/// Each rule is one of the following forms. Values of the matching keys are replaced with the placeholder string
+/// `"[REDACTED]"`, which can be changed by [`Redaction::placeholder`].
+///
+/// - Key name such as `password` matches object keys at any depth. The comparison is case-insensitive
+/// - Glob pattern such as `*token*` matches object keys at any depth case-insensitively. `*` matches any sequence of
+///   characters and `?` matches any single character
+/// - Path starting with `$` such as `$.headers.authorization` matches the value at the path from the root value.
+///   `.key` is a glob pattern for an object key, `[N]` is an index of array, and `.*` and `[*]` match any key and
+///   index. A key containing `.` can be written as `[key.with.dots]`
+///
+/// ```
+/// use tinyjson::{JsonGenerator, JsonValue, Redaction};
+///
+/// let v: JsonValue = r#"
+/// {
+///   "user": {"name": "alice", "Password": "secret"},
+///   "access_token": "xxx",
+///   "headers": [{"authorization": "Bearer xxx"}],
+///   "authorization": "not redacted"
+/// }
+/// "#.parse().unwrap();
+///
+/// let redaction = Redaction::new(&["password", "*_TOKEN", "$.headers[*].authorization"]);
+/// let mut buf = vec![];
+/// JsonGenerator::new(&mut buf).sort_keys(true).redact(&redaction).generate(&v).unwrap();
+/// assert_eq!(
+///     String::from_utf8(buf).unwrap(),
+///     r#"{"access_token":"[REDACTED]","authorization":"not redacted","headers":[{"authorization":"[REDACTED]"}],"user":{"Password":"[REDACTED]","name":"alice"}}"#,
+/// );
+/// ```
+#[derive(Debug)]
+pub struct Redaction<'a> {
+    keys: Vec<&'a str>,
+    paths: Vec<Vec<PathPattern<'a>>>,
+    placeholder: &'a str,
+}
+
+impl<'a> Redaction<'a> {
+    /// Create a new `Redaction` with the rules. See the document of [`Redaction`] for the forms of the rules.
+    pub fn new(rules: &[&'a str]) -> Self {
+        let mut keys = Vec::new();
+        let mut paths = Vec::new();
+        for rule in rules {
+            match rule.strip_prefix('$') {
+                Some(path) => paths.push(parse_path(path)),
+                None => keys.push(*rule),
+            }
+        }
+        Self {
+            keys,
+            paths,
+            placeholder: "[REDACTED]",
+        }
+    }
+
+    /// Set the placeholder string written instead of the redacted values. The default value is `"[REDACTED]"`.
+    ///
+    /// ```
+    /// use tinyjson::{JsonGenerator, JsonValue, Redaction};
+    ///
+    /// let v: JsonValue = r#"{"token": [1, 2, 3]}"#.parse().unwrap();
+    /// let redaction = Redaction::new(&["token"]).placeholder("***");
+    /// let mut buf = vec![];
+    /// JsonGenerator::new(&mut buf).redact(&redaction).generate(&v).unwrap();
+    /// assert_eq!(String::from_utf8(buf).unwrap(), r#"{"token":"***"}"#);
+    /// ```
+    pub fn placeholder(mut self, placeholder: &'a str) -> Self {
+        self.placeholder = placeholder;
+        self
+    }
+
+    pub(crate) fn placeholder_str(&self) -> &'a str {
+        self.placeholder
+    }
+
+    // State at the root value where all path rules are matching
+    pub(crate) fn root(&self) -> RedactionState {
+        let mut paths = BitSet::new(self.paths.len());
+        for i in 0..self.paths.len() {
+            paths.insert(i);
+        }
+        RedactionState { depth: 0, paths }
+    }
+
+    // Returns the state for the child value at the segment and whether the child value should be redacted
+    pub(crate) fn child(&self, state: &RedactionState, seg: Segment<'_>) -> (RedactionState, bool) {
+        let mut redacted = match seg {
+            Segment::Key(key) => self.keys.iter().any(|pat| glob_match(pat, key)),
+            Segment::Index(_) => false,
+        };
+
+        let mut paths = BitSet::new(self.paths.len());
+        for (i, path) in self.paths.iter().enumerate() {
+            if !state.paths.contains(i) {
+                continue;
+            }
+            let matched = match (path.get(state.depth), &seg) {
+                (Some(PathPattern::Key(pat)), Segment::Key(key)) => glob_match(pat, key),
+                (Some(PathPattern::Index(idx)), Segment::Index(i)) => idx == i,
+                (Some(PathPattern::AnyIndex), Segment::Index(_)) => true,
+                _ => false,
+            };
+            if matched {
+                if state.depth + 1 == path.len() {
+                    redacted = true;
+                } else {
+                    paths.insert(i);
+                }
+            }
+        }
+
+        let state = RedactionState {
+            depth: state.depth + 1,
+            paths,
+        };
+        (state, redacted)
+    }
+}
+
+// Set of indices of the path rules. Up to 64 indices are stored without allocation
+#[derive(Clone, Debug)]
+enum BitSet {
+    Inline(u64),
+    Heap(Vec<u64>),
+}
+
+impl BitSet {
+    fn new(len: usize) -> Self {
+        if len <= 64 {
+            BitSet::Inline(0)
+        } else {
+            BitSet::Heap(vec![0; (len - 1) / 64 + 1])
+        }
+    }
+
+    fn contains(&self, i: usize) -> bool {
+        match self {
+            BitSet::Inline(bits) => bits & (1 << i) != 0,
+            BitSet::Heap(words) => words[i / 64] & (1 << (i % 64)) != 0,
+        }
+    }
+
+    fn insert(&mut self, i: usize) {
+        match self {
+            BitSet::Inline(bits) => *bits |= 1 << i,
+            BitSet::Heap(words) => words[i / 64] |= 1 << (i % 64),
+        }
+    }
+}
+
+// State of matching path rules while writing nested values. `paths` is a set of the path rules which match the path
+// to the current value.
+#[derive(Clone, Debug)]
+pub(crate) struct RedactionState {
+    depth: usize,
+    paths: BitSet,
+}
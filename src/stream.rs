@@ -0,0 +1,284 @@
+use crate::parser::{JsonParseError, JsonParseErrorKind, JsonParseResult, JsonParser};
+use crate::JsonValue;
+use std::cell::RefCell;
+use std::io;
+use std::rc::Rc;
+use std::str;
+
+// Error which happened while reading characters. It is reported to the stream via the shared slot since the iterator
+// is owned by the parser. Both sides are always used on the same thread.
+type ReadError = Rc<RefCell<Option<(JsonParseErrorKind, String)>>>;
+
+// Iterator to read UTF-8 characters from `io::Read` object with a fixed size buffer
+struct ReadChars<R: io::Read> {
+    reader: R,
+    buf: Box<[u8]>,
+    start: usize,
+    end: usize,
+    error: ReadError,
+}
+
+impl<R: io::Read> ReadChars<R> {
+    // Ensure the buffer contains at least `n` bytes. Returns `false` when the reader reached EOF.
+    fn fill(&mut self, n: usize) -> io::Result<bool> {
+        if self.end - self.start >= n {
+            return Ok(true);
+        }
+        self.buf.copy_within(self.start..self.end, 0);
+        self.end -= self.start;
+        self.start = 0;
+        while self.end < n {
+            match self.reader.read(&mut self.buf[self.end..]) {
+                Ok(0) => return Ok(false),
+                Ok(len) => self.end += len,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(true)
+    }
+
+    fn read_char(&mut self) -> Result<Option<char>, (JsonParseErrorKind, String)> {
+        let io_err = |err: io::Error| {
+            (
+                JsonParseErrorKind::Io,
+                format!("Could not read input: {}", err),
+            )
+        };
+
+        if !self.fill(1).map_err(io_err)? {
+            return Ok(None);
+        }
+
+        let width = match self.buf[self.start] {
+            b @ 0x00..=0x7f => {
+                self.start += 1;
+                return Ok(Some(b as char));
+            }
+            0xc2..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf4 => 4,
+            b => {
+                let msg = format!("Invalid UTF-8 byte 0x{:x}", b);
+                return Err((JsonParseErrorKind::Encoding, msg));
+            }
+        };
+
+        if !self.fill(width).map_err(io_err)? {
+            let msg = String::from("UTF-8 byte sequence is truncated");
+            return Err((JsonParseErrorKind::Encoding, msg));
+        }
+
+        let bytes = &self.buf[self.start..self.start + width];
+        match str::from_utf8(bytes) {
+            Ok(s) => {
+                self.start += width;
+                Ok(s.chars().next())
+            }
+            Err(err) => {
+                let msg = format!("Invalid UTF-8 byte sequence {:?}: {}", bytes, err);
+                Err((JsonParseErrorKind::Encoding, msg))
+            }
+        }
+    }
+}
+
+impl<R: io::Read> Iterator for ReadChars<R> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        if self.error.borrow().is_some() {
+            return None;
+        }
+        match self.read_char() {
+            Ok(c) => c,
+            Err(err) => {
+                *self.error.borrow_mut() = Some(err);
+                None
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    Start,
+    Elements,
+    Done,
+}
+
+/// Iterator to parse elements of a huge JSON array read from `io::Read` object one by one.
+///
+/// The array is specified by the path of object keys from the root value. When the path is empty, the root value
+/// must be an array. Each element is yielded as a separate `JsonValue` value, so the memory usage does not depend on
+/// the number of elements. Other values around the array are parsed and dropped to validate the syntax of the whole
+/// input. When some error happens, the iterator yields the error and then stops.
+///
+/// ```
+/// use tinyjson::{JsonArrayStream, JsonValue};
+///
+/// let input = r#"
+/// {
+///   "total": 2,
+///   "items": [
+///     {"id": 1},
+///     {"id": 2}
+///   ]
+/// }
+/// "#;
+///
+/// let mut stream = JsonArrayStream::new(input.as_bytes(), &["items"]);
+/// let first = stream.next().unwrap().unwrap();
+/// assert_eq!(first["id"], JsonValue::from(1.0));
+/// let second = stream.next().unwrap().unwrap();
+/// assert_eq!(second["id"], JsonValue::from(2.0));
+/// assert!(stream.next().is_none());
+///
+/// // Syntax errors outside the array are also reported
+/// let mut stream = JsonArrayStream::new(&b"[1, 2] 3"[..], &[]);
+/// assert!(stream.next().unwrap().is_ok());
+/// assert!(stream.next().unwrap().is_ok());
+/// assert!(stream.next().unwrap().is_err());
+/// assert!(stream.next().is_none());
+/// ```
+pub struct JsonArrayStream<R: io::Read> {
+    parser: JsonParser<ReadChars<R>>,
+    error: ReadError,
+    path: Vec<String>,
+    state: State,
+}
+
+impl<R: io::Read> JsonArrayStream<R> {
+    /// Create a new `JsonArrayStream` instance. `path` is a list of object keys to reach the array from the root
+    /// value. The reader does not need to be buffered since this stream has its own buffer.
+    pub fn new(reader: R, path: &[&str]) -> Self {
+        let error = ReadError::default();
+        let chars = ReadChars {
+            reader,
+            buf: vec![0; 8 * 1024].into_boxed_slice(),
+            start: 0,
+            end: 0,
+            error: error.clone(),
+        };
+        Self {
+            parser: JsonParser::new(chars),
+            error,
+            path: path.iter().map(|k| k.to_string()).collect(),
+            state: State::Start,
+        }
+    }
+
+    // Skip to the start of the elements of the array
+    fn enter_array(&mut self) -> Result<bool, JsonParseError> {
+        let mut key = String::new();
+        for (depth, target) in self.path.iter().enumerate() {
+            let path = &self.path[..depth];
+            if self.parser.consume()? != '{' {
+                return self
+                    .parser
+                    .err(format!("Expected object at path {:?}", path));
+            }
+            if self.parser.peek()? == '}' {
+                return self
+                    .parser
+                    .err(format!("Key {:?} was not found at path {:?}", target, path));
+            }
+            loop {
+                self.parser.parse_key(&mut key)?;
+                if &key == target {
+                    break;
+                }
+                self.parser.parse_any()?;
+                if !self.parser.parse_object_delim()? {
+                    return self
+                        .parser
+                        .err(format!("Key {:?} was not found at path {:?}", target, path));
+                }
+            }
+        }
+
+        if self.parser.consume()? != '[' {
+            return self
+                .parser
+                .err(format!("Expected array at path {:?}", self.path));
+        }
+        if self.parser.peek()? == ']' {
+            self.parser.consume()?;
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    // Parse the next element of the array. Returns `None` when the array ended
+    fn next_element(&mut self) -> Result<Option<JsonValue>, JsonParseError> {
+        if self.state == State::Start {
+            if !self.enter_array()? {
+                self.leave_array()?;
+                return Ok(None);
+            }
+            self.state = State::Elements;
+        } else {
+            match self.parser.consume()? {
+                ',' => {}
+                ']' => {
+                    self.leave_array()?;
+                    return Ok(None);
+                }
+                c => {
+                    return self.parser.err(format!(
+                        "',' or ']' is expected for array but actually found '{}'",
+                        c
+                    ))
+                }
+            }
+        }
+        self.parser.parse_any().map(Some)
+    }
+
+    // Parse the rest of the objects which contain the array and check EOF
+    fn leave_array(&mut self) -> Result<(), JsonParseError> {
+        let mut key = String::new();
+        for _ in 0..self.path.len() {
+            while self.parser.parse_object_delim()? {
+                self.parser.parse_key(&mut key)?;
+                self.parser.parse_any()?;
+            }
+        }
+        if let Some(c) = self.parser.next() {
+            return self.parser.err(format!(
+                "Expected EOF but got character '{}'",
+                c.escape_debug(),
+            ));
+        }
+        self.check_read_error()
+    }
+
+    // When reading the input failed, the parser sees the failure as EOF. Report the actual error instead.
+    fn check_read_error(&self) -> Result<(), JsonParseError> {
+        match self.error.borrow_mut().take() {
+            Some((kind, msg)) => self.parser.err_with_kind(kind, msg),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<R: io::Read> Iterator for JsonArrayStream<R> {
+    type Item = JsonParseResult;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.state == State::Done {
+            return None;
+        }
+        match self.next_element() {
+            Ok(Some(elem)) => Some(Ok(elem)),
+            Ok(None) => {
+                self.state = State::Done;
+                None
+            }
+            Err(err) => {
+                self.state = State::Done;
+                Some(self.check_read_error().and(Err(err)))
+            }
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use crate::generator::{FmtWriter, JsonGenerator, NonFinitePolicy};
+use crate::json_value::JsonValue;
+use alloc::string::String;
+use core::fmt;
+
+// Writer to stop writing when the number of characters reaches the budget
+struct Budget<W: fmt::Write> {
+    out: W,
+    rest: usize,
+    exhausted: bool,
+}
+
+impl<W: fmt::Write> fmt::Write for Budget<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.exhausted {
+            return Err(fmt::Error);
+        }
+        for (i, _) in s.char_indices() {
+            if self.rest == 0 {
+                self.exhausted = true;
+                self.out.write_str(&s[..i])?;
+                return Err(fmt::Error);
+            }
+            self.rest -= 1;
+        }
+        self.out.write_str(s)
+    }
+}
+
+/// Truncated summary of a `JsonValue` for logs and error messages. Create it via [`JsonValue::summary`].
+///
+/// The summary is written in one line by `Display`. Unlike the complete output by [`JsonGenerator`], the length of the
+/// summary is limited.
+///
+/// - Arrays and objects deeper than the max depth are written as `[… N more]` and `{… N more}`
+/// - Elements of arrays and members of objects after the max number of items are omitted as `… N more`
+/// - Strings longer than the max length are cut and end with `…`
+/// - When the summary exceeds the character budget, it is cut and ends with `…`
+///
+/// Infinity and NaN are written as `Infinity` and `NaN` literals.
+///
+/// ```
+/// use tinyjson::JsonValue;
+///
+/// let v: JsonValue = r#"[1, 2, 3, 4, 5, [[true]], "long long string"]"#.parse().unwrap();
+/// let s = v.summary().max_items(6).max_depth(2).max_string_len(4).to_string();
+/// assert_eq!(s, "[1,2,3,4,5,[[… 1 more]],… 1 more]");
+///
+/// let s = v.summary().max_string_len(4).to_string();
+/// assert_eq!(s, r#"[1,2,3,4,5,[[true]],"long…"]"#);
+///
+/// let s = v.summary().max_chars(10).to_string();
+/// assert_eq!(s, "[1,2,3,4,5…");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct JsonSummary<'v> {
+    value: &'v JsonValue,
+    max_depth: usize,
+    max_items: usize,
+    max_string_len: usize,
+    max_chars: usize,
+}
+
+impl<'v> JsonSummary<'v> {
+    pub(crate) fn new(value: &'v JsonValue) -> Self {
+        Self {
+            value,
+            max_depth: 3,
+            max_items: 8,
+            max_string_len: 40,
+            max_chars: 200,
+        }
+    }
+
+    /// Set the max depth of nested arrays and objects. The root value is at depth 0. The default value is 3.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Set the max number of elements of each array and members of each object. The default value is 8.
+    pub fn max_items(mut self, items: usize) -> Self {
+        self.max_items = items;
+        self
+    }
+
+    /// Set the max number of characters of each string. The default value is 40.
+    pub fn max_string_len(mut self, len: usize) -> Self {
+        self.max_string_len = len;
+        self
+    }
+
+    /// Set the max number of characters of the whole summary excluding the last `…`. The default value is 200.
+    pub fn max_chars(mut self, chars: usize) -> Self {
+        self.max_chars = chars;
+        self
+    }
+
+    fn write_string<W: fmt::Write>(&self, out: &mut W, s: &str) -> fmt::Result {
+        let mut gen = JsonGenerator::new(FmtWriter(out));
+        let ret = match s.char_indices().nth(self.max_string_len) {
+            Some((idx, _)) => {
+                let mut cut = String::from(&s[..idx]);
+                cut.push('…');
+                gen.write_string(&cut)
+            }
+            None => gen.write_string(s),
+        };
+        ret.map_err(|_| fmt::Error)
+    }
+
+    fn write_more<W: fmt::Write>(&self, out: &mut W, len: usize) -> fmt::Result {
+        if len > self.max_items {
+            if self.max_items > 0 {
+                out.write_str(",")?;
+            }
+            write!(out, "… {} more", len - self.max_items)?;
+        }
+        Ok(())
+    }
+
+    fn write_value<W: fmt::Write>(
+        &self,
+        out: &mut W,
+        value: &JsonValue,
+        depth: usize,
+    ) -> fmt::Result {
+        match value {
+            JsonValue::Array(a) if a.is_empty() => out.write_str("[]"),
+            JsonValue::Array(a) if depth >= self.max_depth => write!(out, "[… {} more]", a.len()),
+            JsonValue::Array(a) => {
+                out.write_str("[")?;
+                for (i, elem) in a.iter().take(self.max_items).enumerate() {
+                    if i > 0 {
+                        out.write_str(",")?;
+                    }
+                    self.write_value(out, elem, depth + 1)?;
+                }
+                self.write_more(out, a.len())?;
+                out.write_str("]")
+            }
+            JsonValue::Object(o) if o.is_empty() => out.write_str("{}"),
+            JsonValue::Object(o) if depth >= self.max_depth => {
+                write!(out, "{{… {} more}}", o.len())
+            }
+            JsonValue::Object(o) => {
+                out.write_str("{")?;
+                for (i, (k, v)) in o.iter().take(self.max_items).enumerate() {
+                    if i > 0 {
+                        out.write_str(",")?;
+                    }
+                    self.write_string(out, k)?;
+                    out.write_str(":")?;
+                    self.write_value(out, v, depth + 1)?;
+                }
+                self.write_more(out, o.len())?;
+                out.write_str("}")
+            }
+            JsonValue::String(s) => self.write_string(out, s),
+            _ => JsonGenerator::new(FmtWriter(out))
+                .non_finite(NonFinitePolicy::WriteLiteral)
+                .write_value(value, 0)
+                .map_err(|_| fmt::Error),
+        }
+    }
+}
+
+impl fmt::Display for JsonSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = Budget {
+            out: &mut *f,
+            rest: self.max_chars,
+            exhausted: false,
+        };
+        match self.write_value(&mut out, self.value, 0) {
+            Err(_) if out.exhausted => f.write_str("…"),
+            ret => ret,
+        }
+    }
+}
@@ -0,0 +1,313 @@
+use crate::generator::{JsonGenerateError, JsonGenerator, JsonWrite};
+use crate::json_value::JsonValue;
+use crate::redaction::{RedactionState, Segment};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+// Array or object which is being written
+struct Container {
+    object: bool,
+    len: usize,
+    has_key: bool,                  // The key was written and the value is expected
+    redacted: bool, // The container was written as the placeholder of `Redaction` and its content is not written
+    parent: Option<RedactionState>, // Redaction state to restore when the container is closed
+}
+
+// Where the next value is written
+enum Slot {
+    Skipped,                         // Inside a redacted container
+    Redacted,                        // The value is replaced with the placeholder
+    Written(Option<RedactionState>), // The value is written. The redaction state should be restored after that
+}
+
+/// Streaming JSON writer which writes values one by one without building the whole `JsonValue` tree in memory.
+///
+/// The output is written with [`JsonGenerator`] so its options such as indentation and escaping are respected. The
+/// sequence of calls is checked at runtime. For example, calling [`JsonWriter::key`] in an array or calling
+/// [`JsonWriter::finish`] with unclosed containers returns an error created by [`JsonWrite::invalid_value`] from
+/// [`JsonGenerateError`] of [`crate::JsonGenerateErrorKind::InvalidSequence`] kind. Such errors can be recovered by
+/// calling the methods in correct order.
+///
+/// Other errors such as I/O errors and values which cannot be represented in JSON leave the output incomplete. After
+/// such an error, all methods including [`JsonWriter::finish`] return an error with the same kind and message.
+///
+/// ```
+/// use tinyjson::{JsonGenerator, JsonWriter, JsonValue};
+///
+/// let mut buf = vec![];
+/// let mut w = JsonWriter::new(&mut buf);
+/// w.begin_object().unwrap();
+/// w.key("name").unwrap();
+/// w.string("tinyjson").unwrap();
+/// w.key("numbers").unwrap();
+/// w.begin_array().unwrap();
+/// for i in 0..3 {
+///     w.number(i as f64).unwrap();
+/// }
+/// w.end().unwrap();
+/// w.key("meta").unwrap();
+/// w.value(&JsonValue::from(vec![true.into(), JsonValue::Null])).unwrap();
+/// w.end().unwrap();
+/// w.finish().unwrap();
+/// assert_eq!(
+///     String::from_utf8(buf).unwrap(),
+///     r#"{"name":"tinyjson","numbers":[0,1,2],"meta":[true,null]}"#,
+/// );
+///
+/// // Options of `JsonGenerator` are available
+/// let mut buf = vec![];
+/// let mut w = JsonWriter::with_generator(JsonGenerator::new(&mut buf).indent("  "));
+/// w.begin_array().unwrap();
+/// w.boolean(true).unwrap();
+/// w.null().unwrap();
+/// w.end().unwrap();
+/// w.finish().unwrap();
+/// assert_eq!(String::from_utf8(buf).unwrap(), "[\n  true,\n  null\n]");
+///
+/// // Key in array is an error
+/// let mut buf = vec![];
+/// let mut w = JsonWriter::new(&mut buf);
+/// w.begin_array().unwrap();
+/// assert!(w.key("oops").is_err());
+/// ```
+pub struct JsonWriter<'a, W: JsonWrite> {
+    gen: JsonGenerator<'a, W>,
+    stack: Vec<Container>,
+    written: bool,                       // The root value was started
+    poisoned: Option<JsonGenerateError>, // Error which left the output incomplete
+    key: String, // The last key of the innermost object to apply `Redaction`
+}
+
+impl<'a, W: JsonWrite> JsonWriter<'a, W> {
+    /// Create a new `JsonWriter` instance which writes JSON text to the given output without indentation.
+    pub fn new(out: W) -> Self {
+        Self::with_generator(JsonGenerator::new(out))
+    }
+
+    /// Create a new `JsonWriter` instance which writes JSON text with the given generator. Options of the generator
+    /// are used for the output. Note that [`JsonGenerator::max_width`] and the options to sort keys are effective only
+    /// for values written by [`JsonWriter::value`]. [`JsonGenerator::redact`] is applied to all values.
+    pub fn with_generator(gen: JsonGenerator<'a, W>) -> Self {
+        Self {
+            gen,
+            stack: Vec::new(),
+            written: false,
+            poisoned: None,
+            key: String::new(),
+        }
+    }
+
+    fn error<T>(msg: &'static str) -> Result<T, JsonGenerateError> {
+        Err(JsonGenerateError::invalid_sequence(msg))
+    }
+
+    // Run the operation unless the writer is poisoned and convert the error into the error type of the output
+    fn run<T, F>(&mut self, f: F) -> Result<T, W::Error>
+    where
+        F: FnOnce(&mut Self) -> Result<T, JsonGenerateError>,
+    {
+        if let Some(err) = &self.poisoned {
+            return Err(W::invalid_value(err.clone()));
+        }
+        f(self).map_err(|err| {
+            if !err.is_invalid_sequence() {
+                self.poisoned = Some(err.clone());
+            }
+            self.gen.error(err)
+        })
+    }
+
+    // Prepare writing a value at the current position
+    fn before_value(&mut self) -> Result<Slot, JsonGenerateError> {
+        let level = self.stack.len();
+        let seg = match self.stack.last_mut() {
+            None if self.written => return Self::error("Root value was already written"),
+            None => {
+                self.written = true;
+                return Ok(Slot::Written(None));
+            }
+            Some(c) if c.object && !c.has_key => {
+                return Self::error("Key must be written before value in object");
+            }
+            Some(c) if c.object => {
+                c.has_key = false;
+                if c.redacted {
+                    return Ok(Slot::Skipped);
+                }
+                Segment::Key(&self.key)
+            }
+            Some(c) => {
+                c.len += 1;
+                if c.redacted {
+                    return Ok(Slot::Skipped);
+                }
+                self.gen.write_separator(c.len == 1, level)?;
+                Segment::Index(c.len - 1)
+            }
+        };
+        Ok(match self.gen.enter_child(seg) {
+            (true, _) => Slot::Redacted,
+            (false, parent) => Slot::Written(parent),
+        })
+    }
+
+    // Write the value at the current position with `f`
+    fn write_with<F>(&mut self, f: F) -> Result<(), JsonGenerateError>
+    where
+        F: FnOnce(&mut JsonGenerator<'a, W>, usize) -> Result<(), JsonGenerateError>,
+    {
+        match self.before_value()? {
+            Slot::Skipped => Ok(()),
+            Slot::Redacted => self.gen.write_placeholder(),
+            Slot::Written(parent) => {
+                let ret = f(&mut self.gen, self.stack.len());
+                self.gen.leave_child(parent);
+                ret
+            }
+        }
+    }
+
+    fn begin(&mut self, object: bool) -> Result<(), JsonGenerateError> {
+        let (redacted, parent) = match self.before_value()? {
+            Slot::Skipped => (true, None),
+            Slot::Redacted => {
+                self.gen.write_placeholder()?;
+                (true, None)
+            }
+            Slot::Written(parent) => {
+                self.gen.write_open(if object { "{" } else { "[" })?;
+                (false, parent)
+            }
+        };
+        self.stack.push(Container {
+            object,
+            len: 0,
+            has_key: false,
+            redacted,
+            parent,
+        });
+        Ok(())
+    }
+
+    /// Start writing an object. Write its entries with [`JsonWriter::key`] and the following value, then close it with
+    /// [`JsonWriter::end`].
+    pub fn begin_object(&mut self) -> Result<(), W::Error> {
+        self.run(|w| w.begin(true))
+    }
+
+    /// Start writing an array. Write its elements as values, then close it with [`JsonWriter::end`].
+    pub fn begin_array(&mut self) -> Result<(), W::Error> {
+        self.run(|w| w.begin(false))
+    }
+
+    /// Write a key of the object being written. This method returns an error when the innermost container is not an
+    /// object or when the previous key has no value.
+    pub fn key(&mut self, key: &str) -> Result<(), W::Error> {
+        self.run(|w| w.write_key(key))
+    }
+
+    fn write_key(&mut self, key: &str) -> Result<(), JsonGenerateError> {
+        let level = self.stack.len();
+        let c = match self.stack.last_mut() {
+            Some(c) if c.object => c,
+            _ => return Self::error("Key must be written in object"),
+        };
+        if c.has_key {
+            return Self::error("Value for the previous key is missing");
+        }
+        c.has_key = true;
+        c.len += 1;
+        if c.redacted {
+            return Ok(());
+        }
+        self.gen.write_separator(c.len == 1, level)?;
+        self.gen.write_key(key)?;
+        if self.gen.redacting() {
+            self.key.clear();
+            self.key.push_str(key);
+        }
+        Ok(())
+    }
+
+    /// Close the innermost array or object.
+    pub fn end(&mut self) -> Result<(), W::Error> {
+        self.run(Self::write_end)
+    }
+
+    fn write_end(&mut self) -> Result<(), JsonGenerateError> {
+        match self.stack.last() {
+            None => return Self::error("No array or object to end"),
+            Some(c) if c.has_key => return Self::error("Value for the last key is missing"),
+            Some(_) => {}
+        }
+        let c = self.stack.pop().unwrap();
+        if c.redacted {
+            return Ok(());
+        }
+        let close = if c.object { "}" } else { "]" };
+        self.gen.write_close(close, c.len == 0, self.stack.len())?;
+        self.gen.leave_child(c.parent);
+        Ok(())
+    }
+
+    /// Write the whole `JsonValue` value as an element of array, a value of object or the root value.
+    pub fn value(&mut self, value: &JsonValue) -> Result<(), W::Error> {
+        self.run(|w| w.write_with(|gen, level| gen.write_value(value, level)))
+    }
+
+    /// Write a string value. This is the same as [`JsonWriter::value`] but does not require allocating `JsonValue`.
+    pub fn string(&mut self, s: &str) -> Result<(), W::Error> {
+        self.run(|w| w.write_with(|gen, _| gen.write_string(s)))
+    }
+
+    /// Write a number value.
+    pub fn number(&mut self, n: f64) -> Result<(), W::Error> {
+        self.value(&JsonValue::Number(n))
+    }
+
+    /// Write a boolean value.
+    pub fn boolean(&mut self, b: bool) -> Result<(), W::Error> {
+        self.value(&JsonValue::Boolean(b))
+    }
+
+    /// Write a null value.
+    pub fn null(&mut self) -> Result<(), W::Error> {
+        self.value(&JsonValue::Null)
+    }
+
+    /// Write the output batched in the internal buffer of the generator. This is necessary only when the buffer is
+    /// enabled by [`JsonGenerator::buffer_size`] and the output should be sent before finishing, for example to
+    /// stream values to a socket.
+    ///
+    /// ```
+    /// use tinyjson::{JsonGenerator, JsonWriter};
+    ///
+    /// let mut buf = vec![];
+    /// let mut w = JsonWriter::with_generator(JsonGenerator::new(&mut buf).buffer_size(1024));
+    /// w.begin_array().unwrap();
+    /// w.number(1.0).unwrap();
+    /// w.flush().unwrap();
+    /// drop(w);
+    /// assert_eq!(buf, b"[1");
+    /// ```
+    pub fn flush(&mut self) -> Result<(), W::Error> {
+        self.run(|w| w.gen.flush())
+    }
+
+    /// Finish writing and return the output. This method returns an error when no value was written or some arrays
+    /// or objects are not closed yet. The output batched in the internal buffer of the generator is written by this
+    /// method even on error.
+    pub fn finish(mut self) -> Result<W, W::Error> {
+        self.flush()?;
+        self.run(|w| {
+            if !w.written {
+                Self::error("No value was written")
+            } else if !w.stack.is_empty() {
+                Self::error("Some arrays or objects are not closed")
+            } else {
+                Ok(())
+            }
+        })?;
+        self.gen.finish()
+    }
+}
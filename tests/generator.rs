@@ -1,6 +1,10 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::f64;
-use tinyjson::JsonValue;
+use std::io;
+use tinyjson::{
+    ColorPalette, FmtWriter, FormatStyle, JsonGenerateError, JsonGenerateErrorKind, JsonGenerator,
+    JsonParser, JsonValue, JsonWriter, NonFinitePolicy, Redaction,
+};
 
 #[test]
 fn test_number() {
@@ -10,6 +14,102 @@ fn test_number() {
     assert_eq!(&JsonValue::Number(-10.0).stringify().unwrap(), "-10");
 }
 
+#[test]
+fn test_number_ecmascript_format() {
+    // Expected strings were generated by `String(n)` in JavaScript
+    for (n, expected) in &[
+        (-0.0, "0"),
+        (0.1, "0.1"),
+        (123.456, "123.456"),
+        (1e21, "1e+21"),
+        (1e20, "100000000000000000000"),
+        (123e18, "123000000000000000000"),
+        (1e-6, "0.000001"),
+        (1e-7, "1e-7"),
+        (1.5e-7, "1.5e-7"),
+        (0.000123, "0.000123"),
+        (1e300, "1e+300"),
+        (1e-300, "1e-300"),
+        (5e-324, "5e-324"),
+        (1.7976931348623157e308, "1.7976931348623157e+308"),
+        (-1.5, "-1.5"),
+        (-1.5e-10, "-1.5e-10"),
+        (9007199254740992.0, "9007199254740992"),
+        (0.1 + 0.2, "0.30000000000000004"),
+        (1234567.125, "1234567.125"),
+        (12345678901234567890.0, "12345678901234567000"),
+    ] {
+        let s = JsonValue::Number(*n).stringify().unwrap();
+        assert_eq!(&s, expected, "number: {:?}", n);
+        let parsed: f64 = s.parse().unwrap();
+        assert_eq!(parsed.to_bits(), (*n + 0.0).to_bits(), "number: {:?}", n);
+    }
+}
+
+#[test]
+fn test_number_options() {
+    let v = JsonValue::from(vec![
+        1.0.into(),
+        (-0.0).into(),
+        1.005.into(),
+        (-2.5).into(),
+        1e21.into(),
+        1e-7.into(),
+    ]);
+
+    assert_eq!(
+        generate_with(&v, |g| g).unwrap(),
+        "[1,0,1.005,-2.5,1e+21,1e-7]"
+    );
+    assert_eq!(
+        generate_with(&v, |g| g.fixed_precision(3)).unwrap(),
+        "[1.000,0.000,1.005,-2.500,1000000000000000000000.000,0.000]"
+    );
+    assert_eq!(
+        generate_with(&v, |g| g.fixed_precision(0)).unwrap(),
+        "[1,0,1,-3,1000000000000000000000,0]"
+    );
+    assert_eq!(
+        generate_with(&v, |g| g.whole_numbers_as_integers(true)).unwrap(),
+        "[1,0,1.005,-2.5,1000000000000000000000,1e-7]"
+    );
+    assert_eq!(
+        generate_with(&v, |g| g.fixed_precision(1).whole_numbers_as_integers(true)).unwrap(),
+        "[1,0,1.0,-2.5,1000000000000000000000,0.0]"
+    );
+
+    // Numbers exactly in the middle are rounded half away from zero as `Number.prototype.toFixed`
+    let v = JsonValue::from(vec![
+        2.5.into(),
+        (-2.5).into(),
+        0.5.into(),
+        (-0.4).into(),
+        9.5.into(),
+        99.5.into(),
+    ]);
+    assert_eq!(
+        generate_with(&v, |g| g.fixed_precision(0)).unwrap(),
+        "[3,-3,1,-0,10,100]"
+    );
+    let v = JsonValue::from(vec![
+        0.125.into(),
+        (-0.125).into(),
+        0.375.into(),
+        1.005.into(),
+        0.995.into(),
+        9.999755859375.into(),
+    ]);
+    assert_eq!(
+        generate_with(&v, |g| g.fixed_precision(2)).unwrap(),
+        "[0.13,-0.13,0.38,1.00,0.99,10.00]"
+    );
+    let v = JsonValue::from(vec![9.999755859375.into()]);
+    assert_eq!(
+        generate_with(&v, |g| g.fixed_precision(12)).unwrap(),
+        "[9.999755859375]"
+    );
+}
+
 #[test]
 fn test_invalid_number() {
     assert!(JsonValue::Number(f64::INFINITY).stringify().is_err());
@@ -61,7 +161,7 @@ fn test_array() {
 
 #[test]
 fn test_object() {
-    let mut m = HashMap::new();
+    let mut m = BTreeMap::new();
     m.insert("foo".to_string(), JsonValue::Number(1.0));
     m.insert("bar".to_string(), JsonValue::Boolean(false));
     m.insert("piyo".to_string(), JsonValue::Null);
@@ -72,7 +172,7 @@ fn test_object() {
     assert!(s.contains(r#""bar":false"#));
     assert!(s.contains(r#""piyo":null"#));
     assert!(s.ends_with('}'));
-    let v = JsonValue::Object(HashMap::new());
+    let v = JsonValue::Object(BTreeMap::new());
     let s = v.stringify().unwrap();
     assert_eq!(&s, "{}");
 }
@@ -83,6 +183,24 @@ fn undent(s: &str) -> String {
     s.replace(indent, "\n").trim().to_string()
 }
 
+type Gen<'g, 'b> = JsonGenerator<'g, &'b mut Vec<u8>>;
+
+// Generate the value with the generator configured by `config` and return the output
+fn generate_with<'g, F>(v: &JsonValue, config: F) -> io::Result<String>
+where
+    F: for<'b> FnOnce(Gen<'g, 'b>) -> Gen<'g, 'b>,
+{
+    let mut buf = vec![];
+    config(JsonGenerator::new(&mut buf)).generate(v)?;
+    Ok(String::from_utf8(buf).unwrap())
+}
+
+// Get the generator error wrapped in the I/O error
+fn generate_error(err: &io::Error) -> &JsonGenerateError {
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{:?}", err);
+    err.get_ref().unwrap().downcast_ref().unwrap()
+}
+
 #[test]
 fn test_format_array() {
     let v = JsonValue::Array(vec![
@@ -109,7 +227,7 @@ fn test_format_array() {
         JsonValue::Array(vec![
             JsonValue::Array(vec![
                 {
-                    let mut m = HashMap::new();
+                    let mut m = BTreeMap::new();
                     m.insert("foo".to_string(), JsonValue::String("bar".to_string()));
                     JsonValue::Object(m)
                 },
@@ -146,7 +264,7 @@ fn test_format_array() {
 
 #[test]
 fn test_format_object() {
-    let mut m = HashMap::new();
+    let mut m = BTreeMap::new();
     m.insert("foo".to_string(), JsonValue::Number(1.0));
     m.insert("bar".to_string(), JsonValue::Boolean(false));
     m.insert("piyo".to_string(), JsonValue::Null);
@@ -157,7 +275,980 @@ fn test_format_object() {
     assert!(s.contains(r#"  "bar": false"#));
     assert!(s.contains(r#"  "piyo": null"#));
     assert!(s.ends_with('}'));
-    let v = JsonValue::Object(HashMap::new());
+    let v = JsonValue::Object(BTreeMap::new());
     let s = v.format().unwrap();
     assert_eq!(&s, "{}");
 }
+
+#[test]
+fn test_fmt_writer() {
+    let v: JsonValue = r#"{"foo": [1, "\n", true, null]}"#.parse().unwrap();
+
+    let mut s = String::new();
+    JsonGenerator::new(FmtWriter(&mut s)).generate(&v).unwrap();
+    assert_eq!(s, v.stringify().unwrap());
+
+    let mut s = String::new();
+    JsonGenerator::new(FmtWriter(&mut s))
+        .indent("  ")
+        .generate(&v)
+        .unwrap();
+    assert_eq!(s, v.format().unwrap());
+
+    let mut s = String::new();
+    let err = JsonGenerator::new(FmtWriter(&mut s))
+        .generate(&JsonValue::Number(f64::NAN))
+        .unwrap_err();
+    assert_eq!(err.message(), "JSON cannot represent NaN");
+}
+
+#[test]
+fn test_key_order() {
+    let v: JsonValue = r#"{"b": {"y": 1, "x": [{"d": 1, "c": 2}]}, "c": true, "a": null, "version": 1, "name": "x"}"#
+        .parse()
+        .unwrap();
+
+    assert_eq!(
+        generate_with(&v, |g| g.sort_keys(true)).unwrap(),
+        r#"{"a":null,"b":{"x":[{"c":2,"d":1}],"y":1},"c":true,"name":"x","version":1}"#,
+    );
+
+    assert_eq!(
+        generate_with(&v.get::<BTreeMap<_, _>>().unwrap()["b"], |g| g
+            .indent("  ")
+            .sort_keys(true))
+        .unwrap(),
+        undent(
+            r#"
+            {
+              "x": [
+                {
+                  "c": 2,
+                  "d": 1
+                }
+              ],
+              "y": 1
+            }"#
+        ),
+    );
+
+    assert_eq!(
+        generate_with(&v, |g| g.key_priority(&["name", "version", "missing"])).unwrap(),
+        r#"{"name":"x","version":1,"a":null,"b":{"x":[{"c":2,"d":1}],"y":1},"c":true}"#,
+    );
+
+    assert_eq!(
+        generate_with(&v, |g| g.sort_keys_by(|l, r| r.cmp(l))).unwrap(),
+        r#"{"version":1,"name":"x","c":true,"b":{"y":1,"x":[{"d":1,"c":2}]},"a":null}"#,
+    );
+}
+
+// Test vectors from RFC 8785
+#[test]
+fn test_canonicalize() {
+    // Section 3.2.2
+    let input = r#"
+    {
+      "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+      "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+      "literals": [null, true, false]
+    }
+    "#;
+    let v: JsonValue = input.parse().unwrap();
+    assert_eq!(
+        v.canonicalize().unwrap(),
+        r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#,
+    );
+
+    // Section 3.2.3
+    let input = r#"
+    {
+      "\u20ac": "Euro Sign",
+      "\r": "Carriage Return",
+      "\ufb33": "Hebrew Letter Dalet With Dagesh",
+      "1": "One",
+      "\ud83d\ude00": "Emoji: Grinning Face",
+      "\u0080": "Control",
+      "\u00f6": "Latin Small Letter O With Diaeresis"
+    }
+    "#;
+    let v: JsonValue = input.parse().unwrap();
+    assert_eq!(
+        tinyjson::canonicalize(&v).unwrap(),
+        "{\"\\r\":\"Carriage Return\",\"1\":\"One\",\"\u{80}\":\"Control\",\"ö\":\"Latin Small Letter O With Diaeresis\",\"€\":\"Euro Sign\",\"😀\":\"Emoji: Grinning Face\",\"\u{fb33}\":\"Hebrew Letter Dalet With Dagesh\"}",
+    );
+
+    // Appendix B
+    for (bits, expected) in &[
+        (0x0000000000000000_u64, "0"),
+        (0x8000000000000000, "0"),
+        (0x0000000000000001, "5e-324"),
+        (0x8000000000000001, "-5e-324"),
+        (0x7fefffffffffffff, "1.7976931348623157e+308"),
+        (0xffefffffffffffff, "-1.7976931348623157e+308"),
+        (0x4340000000000000, "9007199254740992"),
+        (0xc340000000000000, "-9007199254740992"),
+        (0x4430000000000000, "295147905179352830000"),
+        (0x44b52d02c7e14af5, "9.999999999999997e+22"),
+        (0x44b52d02c7e14af6, "1e+23"),
+        (0x44b52d02c7e14af7, "1.0000000000000001e+23"),
+        (0x444b1ae4d6e2ef4e, "999999999999999700000"),
+        (0x444b1ae4d6e2ef4f, "999999999999999900000"),
+        (0x444b1ae4d6e2ef50, "1e+21"),
+        (0x3eb0c6f7a0b5ed8c, "9.999999999999997e-7"),
+        (0x3eb0c6f7a0b5ed8d, "0.000001"),
+        (0x41b3de4355555553, "333333333.3333332"),
+        (0x41b3de4355555554, "333333333.33333325"),
+        (0x41b3de4355555555, "333333333.3333333"),
+        (0x41b3de4355555556, "333333333.3333334"),
+        (0x41b3de4355555557, "333333333.33333343"),
+        (0xbecbf647612f3696, "-0.0000033333333333333333"),
+        (0x43143ff3c1cb0959, "1424953923781206.2"),
+    ] {
+        let v = JsonValue::Number(f64::from_bits(*bits));
+        assert_eq!(&v.canonicalize().unwrap(), expected, "bits: {:x}", bits);
+    }
+
+    for bits in &[
+        0x7fffffffffffffff_u64,
+        0x7ff0000000000000,
+        0xfff0000000000000,
+    ] {
+        let v = JsonValue::from(vec![JsonValue::Number(f64::from_bits(*bits))]);
+        let err = v.canonicalize().unwrap_err();
+        assert!(
+            err.message().starts_with("JSON cannot represent"),
+            "{}",
+            err
+        );
+    }
+}
+
+#[test]
+fn test_ascii_only() {
+    let v: JsonValue = r#"{"k\u00e9y": ["\u0000\n\"\\", "\u0080\u00ff\u0100", "\u3042\uffff", "\ud83d\ude00\udbff\udfff"]}"#
+        .parse()
+        .unwrap();
+
+    let s = generate_with(&v, |g| g.ascii_only(true)).unwrap();
+    assert_eq!(
+        s,
+        r#"{"k\u00e9y":["\u0000\n\"\\","\u0080\u00ff\u0100","\u3042\uffff","\ud83d\ude00\udbff\udfff"]}"#,
+    );
+    assert!(s.is_ascii());
+    assert_eq!(s.parse::<JsonValue>().unwrap(), v);
+
+    let s = generate_with(&v, |g| g.indent("  ").ascii_only(true)).unwrap();
+    assert!(s.is_ascii());
+    assert_eq!(s.parse::<JsonValue>().unwrap(), v);
+}
+
+#[test]
+fn test_html_safe() {
+    let v: JsonValue = r#"{"<key>": "</script>&'\"\u2028\u2029\u3042"}"#.parse().unwrap();
+
+    let s = generate_with(&v, |g| g.html_safe(true)).unwrap();
+    assert_eq!(
+        s,
+        "{\"\\u003ckey\\u003e\":\"\\u003c/script\\u003e\\u0026\\u0027\\\"\\u2028\\u2029\u{3042}\"}",
+    );
+    assert_eq!(s.parse::<JsonValue>().unwrap(), v);
+
+    let s = generate_with(&v, |g| g.html_safe(true).ascii_only(true)).unwrap();
+    assert!(s.ends_with(r#"\u2028\u2029\u3042"}"#), "{}", s);
+    assert_eq!(s.parse::<JsonValue>().unwrap(), v);
+
+    // Not escaped by default
+    let s = v.stringify().unwrap();
+    assert_eq!(s, "{\"<key>\":\"</script>&'\\\"\u{2028}\u{2029}\u{3042}\"}");
+}
+
+#[test]
+fn test_format_style() {
+    let v: JsonValue = r#"{"a": [1, {}], "b": {"c": []}}"#.parse().unwrap();
+    let generate = |style: FormatStyle, indent: &'static str| {
+        generate_with(&v, |g| {
+            let g = g.sort_keys(true).style(style);
+            if indent.is_empty() {
+                g
+            } else {
+                g.indent(indent)
+            }
+        })
+        .unwrap()
+    };
+
+    assert_eq!(
+        generate(FormatStyle::new(), "  "),
+        "{\n  \"a\": [\n    1,\n    {}\n  ],\n  \"b\": {\n    \"c\": []\n  }\n}",
+    );
+    assert_eq!(
+        generate(
+            FormatStyle::new()
+                .space_before_colon(true)
+                .space_after_colon(false)
+                .crlf(true)
+                .trailing_newline(true)
+                .space_in_empty(true),
+            "\t",
+        ),
+        "{\r\n\t\"a\" :[\r\n\t\t1,\r\n\t\t{ }\r\n\t],\r\n\t\"b\" :{\r\n\t\t\"c\" :[ ]\r\n\t}\r\n}\r\n",
+    );
+
+    // Minified output
+    assert_eq!(
+        generate(FormatStyle::new().space_before_colon(true), ""),
+        r#"{"a":[1,{}],"b":{"c":[]}}"#,
+    );
+    assert_eq!(
+        generate(FormatStyle::new().minified_colon_spacing(true), ""),
+        r#"{"a": [1,{}],"b": {"c": []}}"#,
+    );
+    assert_eq!(
+        generate(
+            FormatStyle::new()
+                .space_before_colon(true)
+                .minified_colon_spacing(true)
+                .trailing_newline(true)
+                .space_in_empty(true),
+            "",
+        ),
+        "{\"a\" : [1,{}],\"b\" : {\"c\" : []}}\n",
+    );
+}
+
+#[test]
+fn test_max_width() {
+    let v: JsonValue = r#"{"points": [[1, 2], [3, 4]], "tags": ["a", "b"], "nested": {"x": [], "y": {"z": null}}}"#
+        .parse()
+        .unwrap();
+    let generate = |width: usize| {
+        generate_with(&v, |g| g.indent("  ").sort_keys(true).max_width(width)).unwrap()
+    };
+
+    let inline = r#"{"nested": {"x": [], "y": {"z": null}}, "points": [[1, 2], [3, 4]], "tags": ["a", "b"]}"#;
+    assert_eq!(generate(87), inline);
+    assert_eq!(inline.parse::<JsonValue>().unwrap(), v);
+
+    // The line of "nested" is 40 characters including the indentation and the trailing comma
+    let expected = undent(
+        r#"
+        {
+          "nested": {"x": [], "y": {"z": null}},
+          "points": [[1, 2], [3, 4]],
+          "tags": ["a", "b"]
+        }"#,
+    );
+    assert_eq!(generate(86), expected);
+    assert_eq!(generate(40), expected);
+
+    let expected = undent(
+        r#"
+        {
+          "nested": {
+            "x": [],
+            "y": {"z": null}
+          },
+          "points": [[1, 2], [3, 4]],
+          "tags": ["a", "b"]
+        }"#,
+    );
+    assert_eq!(generate(39), expected);
+    assert_eq!(generate(29), expected);
+
+    let expected = undent(
+        r#"
+        {
+          "nested": {
+            "x": [],
+            "y": {"z": null}
+          },
+          "points": [
+            [1, 2],
+            [3, 4]
+          ],
+          "tags": ["a", "b"]
+        }"#,
+    );
+    assert_eq!(generate(28), expected);
+    assert_eq!(generate(20), expected);
+
+    // Nothing fits
+    let expected = generate_with(&v, |g| g.indent("  ").sort_keys(true)).unwrap();
+    assert_eq!(generate(0), expected);
+}
+
+#[test]
+fn test_json_writer() {
+    let v: JsonValue =
+        r#"{"a": [1, [], {}, {"b": null}], "c": {"d": "\n", "e": [true, false]}, "f": []}"#
+            .parse()
+            .unwrap();
+
+    let write = |w: &mut JsonWriter<'_, &mut Vec<u8>>| {
+        w.begin_object().unwrap();
+        w.key("a").unwrap();
+        w.begin_array().unwrap();
+        w.number(1.0).unwrap();
+        w.begin_array().unwrap();
+        w.end().unwrap();
+        w.begin_object().unwrap();
+        w.end().unwrap();
+        w.begin_object().unwrap();
+        w.key("b").unwrap();
+        w.null().unwrap();
+        w.end().unwrap();
+        w.end().unwrap();
+        w.key("c").unwrap();
+        w.value(&v["c"]).unwrap();
+        w.key("f").unwrap();
+        w.begin_array().unwrap();
+        w.end().unwrap();
+        w.end().unwrap();
+    };
+
+    let mut buf = vec![];
+    let mut w = JsonWriter::new(&mut buf);
+    write(&mut w);
+    w.finish().unwrap();
+    let s = String::from_utf8(buf).unwrap();
+    assert!(s.starts_with(r#"{"a":[1,[],{},{"b":null}],"c":{"#), "{}", s);
+    assert_eq!(s.parse::<JsonValue>().unwrap(), v);
+
+    let mut buf = vec![];
+    let gen = JsonGenerator::new(&mut buf)
+        .indent("  ")
+        .sort_keys(true)
+        .style(FormatStyle::new().trailing_newline(true));
+    let mut w = JsonWriter::with_generator(gen);
+    write(&mut w);
+    w.finish().unwrap();
+    let mut expected = vec![];
+    JsonGenerator::new(&mut expected)
+        .indent("  ")
+        .sort_keys(true)
+        .style(FormatStyle::new().trailing_newline(true))
+        .generate(&v)
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(buf).unwrap(),
+        String::from_utf8(expected).unwrap()
+    );
+
+    let mut s = String::new();
+    let mut w = JsonWriter::new(FmtWriter(&mut s));
+    w.string("hello").unwrap();
+    w.finish().unwrap();
+    assert_eq!(s, r#""hello""#);
+}
+
+#[test]
+fn test_json_writer_error() {
+    let mut buf = vec![];
+    let mut w = JsonWriter::new(&mut buf);
+    w.begin_array().unwrap();
+    assert!(w.key("a").is_err());
+    w.end().unwrap();
+    assert!(w.end().is_err());
+    assert!(w.null().is_err()); // Root value was already written
+    w.finish().unwrap();
+
+    let mut buf = vec![];
+    let mut w = JsonWriter::new(&mut buf);
+    w.begin_object().unwrap();
+    assert!(w.null().is_err()); // Key is missing
+    w.key("a").unwrap();
+    assert!(w.key("b").is_err());
+    assert!(w.end().is_err()); // Value is missing
+    w.null().unwrap();
+    w.end().unwrap();
+    w.finish().unwrap();
+    assert_eq!(String::from_utf8(buf).unwrap(), r#"{"a":null}"#);
+
+    let mut buf = vec![];
+    let mut w = JsonWriter::new(&mut buf);
+    w.begin_object().unwrap();
+    let err = w.finish().unwrap_err();
+    let err = generate_error(&err);
+    assert_eq!(err.kind(), JsonGenerateErrorKind::InvalidSequence);
+    assert_eq!(err.message(), "Some arrays or objects are not closed");
+
+    let mut buf = vec![];
+    let w = JsonWriter::new(&mut buf);
+    let err = w.finish().unwrap_err();
+    assert_eq!(generate_error(&err).message(), "No value was written");
+
+    let mut buf = vec![];
+    let mut w = JsonWriter::new(&mut buf);
+    assert!(w.number(f64::NAN).is_err());
+
+    // Writer is poisoned after the output was left incomplete
+    let mut s = String::new();
+    let mut w = JsonWriter::new(FmtWriter(&mut s));
+    w.begin_array().unwrap();
+    w.number(1.0).unwrap();
+    let err = w.number(f64::NAN).unwrap_err();
+    assert_eq!(err.kind(), JsonGenerateErrorKind::InvalidValue);
+    let err = w.number(2.0).unwrap_err();
+    assert_eq!(err.kind(), JsonGenerateErrorKind::InvalidValue);
+    assert_eq!(err.message(), "JSON cannot represent NaN");
+    assert!(w.end().is_err());
+    assert!(w.finish().is_err());
+    assert!(!s.contains('2'), "{}", s);
+
+    let mut s = String::new();
+    let mut w = JsonWriter::new(FmtWriter(&mut s));
+    w.begin_object().unwrap();
+    w.key("a").unwrap();
+    assert!(w.number(f64::NAN).is_err());
+    assert!(w.key("b").is_err());
+    assert!(w.number(2.0).is_err());
+    assert!(w.end().is_err());
+    let err = w.finish().err().unwrap();
+    assert_eq!(err.message(), "JSON cannot represent NaN");
+    assert_eq!(s, r#"{"a":"#);
+}
+
+#[test]
+fn test_non_finite_policy() {
+    let v = JsonValue::from(vec![
+        f64::NAN.into(),
+        f64::INFINITY.into(),
+        f64::NEG_INFINITY.into(),
+        1.5.into(),
+    ]);
+    let generate = |policy: NonFinitePolicy| generate_with(&v, |g| g.non_finite(policy));
+
+    let err = generate(NonFinitePolicy::Error).unwrap_err();
+    assert_eq!(generate_error(&err).message(), "JSON cannot represent NaN");
+    assert_eq!(
+        generate(NonFinitePolicy::WriteNull).unwrap(),
+        "[null,null,null,1.5]"
+    );
+    assert_eq!(
+        generate(NonFinitePolicy::WriteAsString).unwrap(),
+        r#"["NaN","Infinity","-Infinity",1.5]"#,
+    );
+    let s = generate(NonFinitePolicy::WriteLiteral).unwrap();
+    assert_eq!(s, "[NaN,Infinity,-Infinity,1.5]");
+
+    // Read the literals back
+    assert!(s.parse::<JsonValue>().is_err());
+    let parsed = JsonParser::new(s.chars())
+        .allow_non_finite(true)
+        .parse()
+        .unwrap();
+    let a: &Vec<_> = parsed.get().unwrap();
+    assert!(a[0].get::<f64>().unwrap().is_nan());
+    assert_eq!(&a[1..], &v.get::<Vec<_>>().unwrap()[1..]);
+}
+
+#[test]
+fn test_generate_error_path() {
+    let v: JsonValue = r#"{"series": [{"points": [1, 2, 3, 4]}], "a b": {"c": [[1]]}}"#
+        .parse()
+        .unwrap();
+
+    let mut v1 = v.clone();
+    v1["series"][0]["points"][3] = f64::NAN.into();
+    let mut v2 = v.clone();
+    v2["a b"]["c"][0][0] = f64::INFINITY.into();
+
+    for indent in [None, Some("  ")] {
+        let generate = |v: &JsonValue| {
+            generate_with(v, |g| match indent {
+                Some(indent) => g.indent(indent).max_width(80),
+                None => g,
+            })
+            .unwrap_err()
+        };
+
+        let err = generate(&v1);
+        let err = generate_error(&err);
+        assert_eq!(err.kind(), JsonGenerateErrorKind::InvalidValue);
+        assert_eq!(err.path(), "$.series[0].points[3]");
+        assert_eq!(
+            err.to_string(),
+            "Generate error: JSON cannot represent NaN at $.series[0].points[3]",
+        );
+
+        let err = generate(&v2);
+        let err = generate_error(&err);
+        assert_eq!(err.path(), r#"$["a b"].c[0][0]"#);
+        assert_eq!(err.message(), "JSON cannot represent inf");
+    }
+
+    let err = JsonValue::from(f64::NAN).stringify().unwrap_err();
+    assert_eq!(err.path(), "$");
+}
+
+#[test]
+fn test_generate_error_io() {
+    struct Broken;
+    impl io::Write for Broken {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::BrokenPipe, "broken"))
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let v: JsonValue = "[1, 2]".parse().unwrap();
+    // The original I/O error is returned
+    let err = v.write_to(&mut Broken).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+    assert_eq!(err.to_string(), "broken");
+
+    let mut w = JsonWriter::new(Broken);
+    let err = w.begin_array().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+
+    // Errors which are not caused by the output are wrapped in `io::Error`
+    let v = JsonValue::from(vec![f64::NAN.into()]);
+    let err = v.write_to(&mut vec![]).unwrap_err();
+    assert_eq!(generate_error(&err).path(), "$[0]");
+    let err = v.format_to(&mut vec![]).unwrap_err();
+    assert_eq!(
+        generate_error(&err).kind(),
+        JsonGenerateErrorKind::InvalidValue
+    );
+
+    // `JsonGenerateError` can be converted into `io::Error` with `?` operator
+    fn write(v: &JsonValue) -> io::Result<String> {
+        Ok(v.stringify()?)
+    }
+    let err = write(&v).unwrap_err();
+    assert_eq!(generate_error(&err).message(), "JSON cannot represent NaN");
+}
+
+#[test]
+fn test_display() {
+    let v: JsonValue = r#"{"foo": [1, "\n", true, null, {}]}"#.parse().unwrap();
+    assert_eq!(format!("{}", v), v.stringify().unwrap());
+    assert_eq!(v.to_string(), v.stringify().unwrap());
+    assert_eq!(format!("{:#}", v), v.format().unwrap());
+
+    let v = JsonValue::from(vec![
+        1.0.into(),
+        f64::NAN.into(),
+        f64::INFINITY.into(),
+        f64::NEG_INFINITY.into(),
+    ]);
+    assert_eq!(v.to_string(), "[1,null,null,null]");
+    assert_eq!(format!("{:#}", v), "[\n  1,\n  null,\n  null,\n  null\n]");
+    assert_eq!(format!("{}", JsonValue::from(f64::NAN)), "null");
+}
+
+#[test]
+fn test_color() {
+    fn strip(s: &str) -> String {
+        let mut out = String::new();
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                assert_eq!(chars.next(), Some('['));
+                for c in chars.by_ref() {
+                    if c == 'm' {
+                        break;
+                    }
+                }
+            } else {
+                out.push(c);
+            }
+        }
+        out
+    }
+
+    let v: JsonValue = r#"
+    {
+      "num": [1, -2.5, 1e100],
+      "str": "hello\nworld",
+      "nested": {"bools": [true, false], "null": null, "empty": [], "obj": {}}
+    }
+    "#
+    .parse()
+    .unwrap();
+
+    let configs: [for<'b> fn(Gen<'static, 'b>) -> Gen<'static, 'b>; 4] = [
+        |g| g,
+        |g| g.indent("  "),
+        |g| g.indent("    ").max_width(30).sort_keys(true),
+        |g| {
+            g.indent("\t").style(
+                FormatStyle::new()
+                    .space_before_colon(true)
+                    .space_in_empty(true),
+            )
+        },
+    ];
+    for config in configs.iter() {
+        let plain = generate_with(&v, config).unwrap();
+        let colored = generate_with(&v, |g| config(g).color(ColorPalette::new())).unwrap();
+        assert_ne!(plain, colored);
+        assert_eq!(strip(&colored), plain);
+    }
+
+    let v: JsonValue = r#"{"k": ["s", 1, true, null]}"#.parse().unwrap();
+    let palette = ColorPalette::plain()
+        .key("1")
+        .string("2")
+        .number("3")
+        .boolean("4")
+        .null("5")
+        .punctuation("6");
+    assert_eq!(
+        generate_with(&v, |g| g.color(palette)).unwrap(),
+        "\x1b[6m{\x1b[0m\x1b[1m\"k\"\x1b[0m\x1b[6m:\x1b[0m\x1b[6m[\x1b[0m\x1b[2m\"s\"\x1b[0m\x1b[6m,\x1b[0m\
+         \x1b[3m1\x1b[0m\x1b[6m,\x1b[0m\x1b[4mtrue\x1b[0m\x1b[6m,\x1b[0m\x1b[5mnull\x1b[0m\x1b[6m]\x1b[0m\x1b[6m}\x1b[0m",
+    );
+
+    // Nothing is colored with the plain palette
+    assert_eq!(
+        generate_with(&v, |g| g.color(ColorPalette::plain())).unwrap(),
+        v.stringify().unwrap(),
+    );
+
+    // Streaming writer also colors the output
+    let mut buf = vec![];
+    let gen = JsonGenerator::new(&mut buf)
+        .indent("  ")
+        .color(ColorPalette::new());
+    let mut w = JsonWriter::with_generator(gen);
+    w.begin_object().unwrap();
+    w.key("k").unwrap();
+    w.begin_array().unwrap();
+    w.string("s").unwrap();
+    w.number(1.0).unwrap();
+    w.boolean(true).unwrap();
+    w.null().unwrap();
+    w.end().unwrap();
+    w.end().unwrap();
+    w.finish().unwrap();
+    assert_eq!(strip(&String::from_utf8(buf).unwrap()), v.format().unwrap());
+}
+
+#[test]
+fn test_json5() {
+    let v: JsonValue = r#"
+    {
+      "name": "it's \"tinyjson\"",
+      "$id_1": [1, 2, {}],
+      "1st": null,
+      "with space": {"a": []},
+      "café": true
+    }
+    "#
+    .parse()
+    .unwrap();
+    let mut v = v;
+    v["$id_1"][1] = f64::NAN.into();
+
+    let generate = |config: &dyn for<'b> Fn(Gen<'static, 'b>) -> Gen<'static, 'b>| {
+        generate_with(&v, |g| config(g.json5(true).sort_keys(true))).unwrap()
+    };
+
+    assert_eq!(
+        generate(&|g| g),
+        r#"{$id_1:[1,NaN,{}],"1st":null,"café":true,name:"it's \"tinyjson\"","with space":{a:[]}}"#,
+    );
+    assert_eq!(
+        generate(&|g| g.single_quotes(true)),
+        r#"{$id_1:[1,NaN,{}],'1st':null,'café':true,name:'it\'s "tinyjson"','with space':{a:[]}}"#,
+    );
+    assert_eq!(
+        generate(&|g| g.indent("  ")),
+        undent(
+            r#"
+            {
+              $id_1: [
+                1,
+                NaN,
+                {},
+              ],
+              "1st": null,
+              "café": true,
+              name: "it's \"tinyjson\"",
+              "with space": {
+                a: [],
+              },
+            }"#
+        ),
+    );
+    // Inline containers have no trailing comma
+    assert_eq!(
+        generate(&|g| g.indent("  ").max_width(30)),
+        undent(
+            r#"
+            {
+              $id_1: [1, NaN, {}],
+              "1st": null,
+              "café": true,
+              name: "it's \"tinyjson\"",
+              "with space": {a: []},
+            }"#
+        ),
+    );
+    // Explicit non-finite policy is respected
+    assert!(
+        generate(&|g| g.non_finite(NonFinitePolicy::WriteNull)).starts_with("{$id_1:[1,null,{}]")
+    );
+    // Single quotes are escaped as unicode escapes with html_safe
+    assert!(generate(&|g| g.single_quotes(true).html_safe(true))
+        .contains(r#"name:'it\u0027s "tinyjson"'"#));
+    // Single quotes are ignored without JSON5 mode
+    assert_eq!(
+        generate_with(&JsonValue::from("'".to_string()), |g| g.single_quotes(true)).unwrap(),
+        r#""'""#,
+    );
+
+    // Streaming writer
+    let mut buf = vec![];
+    let mut w = JsonWriter::with_generator(JsonGenerator::new(&mut buf).indent("  ").json5(true));
+    w.begin_object().unwrap();
+    w.key("a").unwrap();
+    w.begin_array().unwrap();
+    w.number(f64::INFINITY).unwrap();
+    w.end().unwrap();
+    w.key("b c").unwrap();
+    w.begin_array().unwrap();
+    w.end().unwrap();
+    w.end().unwrap();
+    w.finish().unwrap();
+    assert_eq!(
+        String::from_utf8(buf).unwrap(),
+        undent(
+            r#"
+            {
+              a: [
+                Infinity,
+              ],
+              "b c": [],
+            }"#
+        ),
+    );
+}
+
+#[test]
+fn test_redaction() {
+    let v: JsonValue = r#"
+    {
+      "user": {"name": "alice", "PassWord": "secret", "tokens": [1, 2]},
+      "api_token": "xxx",
+      "authorization": "top level",
+      "headers": [{"authorization": "a"}, {"authorization": "b"}],
+      "items": [{"secret": 1}, {"secret": 2}],
+      "a.b": {"c": 1},
+      "パスワード": "秘密"
+    }
+    "#
+    .parse()
+    .unwrap();
+    let original = v.clone();
+
+    let redaction = Redaction::new(&[
+        "password",
+        "*_TOKEN",
+        "$.headers[*].authorization",
+        "$.items[1].secret",
+        "$[a.b].c",
+        "パスワード",
+    ]);
+    assert_eq!(
+        generate_with(&v, |g| g.sort_keys(true).redact(&redaction)).unwrap(),
+        concat!(
+            r#"{"a.b":{"c":"[REDACTED]"},"api_token":"[REDACTED]","authorization":"top level","#,
+            r#""headers":[{"authorization":"[REDACTED]"},{"authorization":"[REDACTED]"}],"#,
+            r#""items":[{"secret":1},{"secret":"[REDACTED]"}],"#,
+            r#""user":{"PassWord":"[REDACTED]","name":"alice","tokens":[1,2]},"パスワード":"[REDACTED]"}"#,
+        ),
+    );
+    assert_eq!(v, original);
+
+    // Glob patterns
+    let redaction = Redaction::new(&["*TOKEN*", "$.*.n?me"]).placeholder("***");
+    let s = generate_with(&v["user"], |g| {
+        g.indent("  ")
+            .max_width(80)
+            .sort_keys(true)
+            .redact(&redaction)
+    })
+    .unwrap();
+    assert_eq!(
+        s,
+        r#"{"PassWord": "secret", "name": "alice", "tokens": "***"}"#,
+    );
+    let s = generate_with(&v, |g| {
+        g.indent("  ")
+            .max_width(80)
+            .sort_keys(true)
+            .redact(&redaction)
+    })
+    .unwrap();
+    assert!(
+        s.contains(r#""user": {"PassWord": "secret", "name": "***", "tokens": "***"}"#),
+        "{}",
+        s
+    );
+    assert!(s.contains(r#""api_token": "***""#), "{}", s);
+
+    // Redacted values never cause errors
+    let v = JsonValue::from(vec![vec![f64::NAN.into()].into()]);
+    let redaction = Redaction::new(&["$[0]"]);
+    assert_eq!(
+        generate_with(&v, |g| g.redact(&redaction)).unwrap(),
+        r#"["[REDACTED]"]"#,
+    );
+
+    // More than 64 path rules
+    let rules: Vec<String> = (0..100).map(|i| format!("$[{}]", i)).collect();
+    let rules: Vec<&str> = rules.iter().map(String::as_str).collect();
+    let redaction = Redaction::new(&rules[1..]);
+    let v = JsonValue::from(
+        (0..101)
+            .map(|i| JsonValue::from(i as f64))
+            .collect::<Vec<_>>(),
+    );
+    let s = generate_with(&v, |g| g.redact(&redaction)).unwrap();
+    assert!(s.starts_with(r#"[0,"[REDACTED]","[REDACTED]","#), "{}", s);
+    assert!(s.ends_with(r#","[REDACTED]",100]"#), "{}", s);
+    assert_eq!(s.matches("[REDACTED]").count(), 99);
+}
+
+#[test]
+fn test_redaction_json_writer() {
+    let redaction = Redaction::new(&["password", "$.users[*].token", "$.items[1]"]);
+    let mut buf = vec![];
+    let gen = JsonGenerator::new(&mut buf).redact(&redaction);
+    let mut w = JsonWriter::with_generator(gen);
+    w.begin_object().unwrap();
+    w.key("password").unwrap();
+    w.string("secret").unwrap();
+    w.key("users").unwrap();
+    w.begin_array().unwrap();
+    for name in ["alice", "bob"] {
+        w.begin_object().unwrap();
+        w.key("name").unwrap();
+        w.string(name).unwrap();
+        w.key("token").unwrap();
+        w.begin_array().unwrap();
+        w.number(1.0).unwrap();
+        w.begin_object().unwrap();
+        w.key("x").unwrap();
+        w.null().unwrap();
+        w.end().unwrap();
+        w.end().unwrap();
+        w.end().unwrap();
+    }
+    w.end().unwrap();
+    w.key("token").unwrap();
+    w.string("not redacted").unwrap();
+    w.key("items").unwrap();
+    w.begin_array().unwrap();
+    w.number(1.0).unwrap();
+    w.number(f64::NAN).unwrap(); // Redacted values never cause errors
+    w.value(&JsonValue::from(vec![JsonValue::Null])).unwrap();
+    w.end().unwrap();
+    w.key("nested").unwrap();
+    w.value(&r#"{"Password": 1}"#.parse().unwrap()).unwrap();
+    w.end().unwrap();
+    w.finish().unwrap();
+    assert_eq!(
+        String::from_utf8(buf).unwrap(),
+        concat!(
+            r#"{"password":"[REDACTED]","users":[{"name":"alice","token":"[REDACTED]"},"#,
+            r#"{"name":"bob","token":"[REDACTED]"}],"token":"not redacted","#,
+            r#""items":[1,"[REDACTED]",[null]],"nested":{"Password":"[REDACTED]"}}"#,
+        ),
+    );
+}
+
+#[test]
+fn test_buffering() {
+    use std::io;
+
+    // Writer which records each write call
+    #[derive(Default)]
+    struct Writes(Vec<Vec<u8>>);
+    impl io::Write for Writes {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.push(buf.to_vec());
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let v: JsonValue = r#"[1, "foo", {"bar": [true, null]}, "long long long long string"]"#
+        .parse()
+        .unwrap();
+    let expected = v.stringify().unwrap();
+
+    let mut w = Writes::default();
+    JsonGenerator::new(&mut w)
+        .buffer_size(8 * 1024)
+        .generate(&v)
+        .unwrap();
+    assert_eq!(w.0, vec![expected.as_bytes().to_vec()]);
+
+    // Not buffered by default
+    let mut w = Writes::default();
+    JsonGenerator::new(&mut w).generate(&v).unwrap();
+    assert!(w.0.len() > 10);
+    assert_eq!(w.0.concat(), expected.as_bytes());
+
+    // Buffer is flushed when it is full. Long strings are written directly
+    let mut w = Writes::default();
+    JsonGenerator::new(&mut w)
+        .buffer_size(16)
+        .generate(&v)
+        .unwrap();
+    assert!(w
+        .0
+        .iter()
+        .all(|b| b.len() <= 16 || b == b"long long long long string"));
+    assert_eq!(w.0.concat(), expected.as_bytes());
+
+    // Output written before the error is flushed
+    let mut buf = vec![];
+    let err = JsonGenerator::new(&mut buf)
+        .buffer_size(8 * 1024)
+        .generate(&JsonValue::from(vec![1.0.into(), f64::NAN.into()]))
+        .unwrap_err();
+    assert_eq!(
+        generate_error(&err).kind(),
+        JsonGenerateErrorKind::InvalidValue
+    );
+    assert_eq!(buf, b"[1,");
+
+    // Streaming writer flushes the buffer at `flush` and `finish`
+    let mut w = Writes::default();
+    let gen = JsonGenerator::new(&mut w).buffer_size(8 * 1024);
+    let mut writer = JsonWriter::with_generator(gen);
+    writer.begin_array().unwrap();
+    for i in 0..100 {
+        writer.number(i as f64).unwrap();
+    }
+    writer.flush().unwrap();
+    writer.end().unwrap();
+    writer.finish().unwrap();
+    assert_eq!(w.0.len(), 2);
+    assert_eq!(w.0[0].len(), 290);
+    assert_eq!(w.0[1], b"]");
+
+    // The buffered output is written even when `finish` fails
+    let mut buf = vec![];
+    let mut writer = JsonWriter::with_generator(JsonGenerator::new(&mut buf).buffer_size(1024));
+    writer.begin_array().unwrap();
+    writer.null().unwrap();
+    assert!(writer.finish().is_err());
+    assert_eq!(buf, b"[null");
+
+    // Streaming writer is not buffered by default
+    let mut w = Writes::default();
+    let mut writer = JsonWriter::new(&mut w);
+    writer.begin_array().unwrap();
+    writer.null().unwrap();
+    assert_eq!(w.0.concat(), b"[null");
+}
@@ -88,6 +88,88 @@ fn test_number_success_edge_cases() {
     assert_eq!(n, 0.1);
 }
 
+#[test]
+fn test_number_same_as_std() {
+    let mut literals: Vec<String> = [
+        "9007199254740992",
+        "9007199254740993",
+        "-9007199254740993",
+        "18446744073709551615",
+        "18446744073709551616",
+        "1234567890123456789",
+        "12345678901234567890",
+        "0.1",
+        "0.30000000000000004",
+        "1e22",
+        "1e23",
+        "12e25",
+        "9007199254740991e15",
+        "1e-22",
+        "1e-23",
+        "2.2250738585072014e-308",
+        "2.2250738585072011e-308",
+        "4.9406564584124654e-324",
+        "1.7976931348623157e308",
+        "1.7976931348623159e308",
+        "0.000000000000000000000000000001",
+        "1e400",
+        "-1e-400",
+        "1e99999999999999999999",
+        "1e-99999999999999999999",
+        "0e99999999999999999999",
+        "-0.0e-5",
+        "100000000000000000000000000000000000000000000000000e-50",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+
+    // Generate various number literals with a simple linear congruential generator
+    let mut state = 0x2545f4914f6cdd1d_u64;
+    let mut rand = move |n: u64| {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (state >> 33) % n
+    };
+    for _ in 0..10000 {
+        let mut s = String::new();
+        if rand(2) == 0 {
+            s.push('-');
+        }
+        let int_len = rand(20) + 1;
+        s.push_str(&(rand(9) + 1).to_string());
+        for _ in 1..int_len {
+            s.push_str(&rand(10).to_string());
+        }
+        if rand(2) == 0 {
+            s.push('.');
+            for _ in 0..rand(20) + 1 {
+                s.push_str(&rand(10).to_string());
+            }
+        }
+        if rand(2) == 0 {
+            s.push('e');
+            s.push_str(["", "+", "-"][rand(3) as usize]);
+            s.push_str(&rand(350).to_string());
+        }
+        literals.push(s);
+    }
+
+    for lit in &literals {
+        let expected: f64 = lit.parse().unwrap();
+        let actual: f64 = lit.parse::<JsonValue>().unwrap().try_into().unwrap();
+        assert_eq!(
+            actual.to_bits(),
+            expected.to_bits(),
+            "literal {:?}: {} vs {}",
+            lit,
+            actual,
+            expected,
+        );
+    }
+}
+
 #[test]
 fn test_number_failure_edge_cases() {
     let parsed: JsonParseResult = r#"01"#.parse();
@@ -115,3 +197,470 @@ fn test_number_failure_edge_cases() {
     let parsed: JsonParseResult = r#"+1"#.parse();
     parsed.unwrap_err();
 }
+
+#[test]
+fn test_parse_bytes_with_bom() {
+    let expected: JsonValue = r#"{"foo": [1, "€"]}"#.parse().unwrap();
+    let utf8 = r#"{"foo": [1, "€"]}"#;
+
+    let mut input = b"\xef\xbb\xbf".to_vec();
+    input.extend_from_slice(utf8.as_bytes());
+    assert_eq!(parse_bytes(&input).unwrap(), expected);
+
+    let mut input = vec![0xff, 0xfe];
+    input.extend(utf8.encode_utf16().flat_map(u16::to_le_bytes));
+    assert_eq!(parse_bytes(&input).unwrap(), expected);
+
+    let mut input = vec![0xfe, 0xff];
+    input.extend(utf8.encode_utf16().flat_map(u16::to_be_bytes));
+    assert_eq!(parse_bytes(&input).unwrap(), expected);
+
+    let mut input = vec![0xff, 0xfe, 0x00, 0x00];
+    input.extend(utf8.chars().flat_map(|c| (c as u32).to_le_bytes()));
+    assert_eq!(parse_bytes(&input).unwrap(), expected);
+
+    let mut input = vec![0x00, 0x00, 0xfe, 0xff];
+    input.extend(utf8.chars().flat_map(|c| (c as u32).to_be_bytes()));
+    assert_eq!(parse_bytes(&input).unwrap(), expected);
+}
+
+#[test]
+fn test_parse_bytes_without_bom() {
+    for src in ["[1, true]", "1", "12", r#""€""#] {
+        let expected: JsonValue = src.parse().unwrap();
+        let inputs = [
+            src.as_bytes().to_vec(),
+            src.encode_utf16().flat_map(u16::to_le_bytes).collect(),
+            src.encode_utf16().flat_map(u16::to_be_bytes).collect(),
+            src.chars().flat_map(|c| (c as u32).to_le_bytes()).collect(),
+            src.chars()
+                .flat_map(|c| (c as u32).to_be_bytes())
+                .collect::<Vec<_>>(),
+        ];
+        for input in inputs.iter() {
+            assert_eq!(parse_bytes(input).unwrap(), expected, "input: {:?}", input);
+        }
+    }
+}
+
+#[test]
+fn test_parse_bytes_invalid_encoding() {
+    let err = parse_bytes(b"[1,\n \xff]").unwrap_err();
+    assert_eq!((err.line(), err.column()), (2, 2));
+    // Unpaired surrogate in UTF-16LE
+    assert!(parse_bytes(b"\"\x00\x00\xd8\"\x00").is_err());
+    // Truncated UTF-16LE
+    assert!(parse_bytes(b"1\x002").is_err());
+    // Out of range code point in UTF-32BE
+    assert!(parse_bytes(b"\x00\x00\x00\"\x00\x11\x00\x00\x00\x00\x00\"").is_err());
+}
+
+#[test]
+fn test_parse_bytes_strict() {
+    assert_eq!(
+        parse_bytes_strict(b"[1]").unwrap(),
+        JsonValue::from(vec![1.0.into()])
+    );
+    let err = parse_bytes_strict(b"\xef\xbb\xbf[1]").unwrap_err();
+    assert!(format!("{}", err).contains("Invalid character"), "{}", err);
+    assert!(parse_bytes_strict(b"\xff\xfe[\x001\x00]\x00").is_err());
+}
+
+fn parse_into(input: &str, value: &mut JsonValue) {
+    JsonParser::new(input.chars()).parse_into(value).unwrap();
+    let expected: JsonValue = input.parse().unwrap();
+    assert_eq!(value, &expected, "input: {:?}", input);
+}
+
+#[test]
+fn test_parse_into() {
+    let mut v = JsonValue::Null;
+    for input in [
+        r#"{"a": 1, "b": ["foo", {"c": null}], "d": "bar"}"#,
+        r#"{"a": 2, "b": ["piyo", {"c": true}], "d": "baz"}"#,
+        r#"{"b": [{"c": false}], "e": {}}"#,
+        r#"{"a": 1, "a": 2, "b": 3}"#,
+        r#"{"a": {"x": 1}, "c": 1, "a": [2], "c": 2, "a": 3}"#,
+        r#"{"b": 1, "d": [], "d": {}, "e": 2, "b": "x"}"#,
+        r#"{}"#,
+        r#"[1, [2, 3], "foo", {"x": [4]}]"#,
+        r#"[true, [2], "foo"]"#,
+        r#"[]"#,
+        r#""hello""#,
+        r#"42"#,
+        r#"null"#,
+    ] {
+        parse_into(input, &mut v);
+    }
+}
+
+#[test]
+fn test_parse_into_reuse_allocations() {
+    let mut v: JsonValue = r#"{"id": "xxxxxxxx", "items": [1, 2, 3, 4], "stale": true}"#
+        .parse()
+        .unwrap();
+    let id_ptr = v["id"].get::<String>().unwrap().as_ptr();
+    let items_ptr = v["items"].get::<Vec<_>>().unwrap().as_ptr();
+
+    parse_into(r#"{"id": "yyyy", "items": [5, 6]}"#, &mut v);
+    assert_eq!(v["id"].get::<String>().unwrap().as_ptr(), id_ptr);
+    assert_eq!(v["items"].get::<Vec<_>>().unwrap().as_ptr(), items_ptr);
+    assert!(v["items"].get::<Vec<_>>().unwrap().capacity() >= 4);
+}
+
+#[test]
+fn test_parse_into_error() {
+    let mut v = JsonValue::Null;
+    let err = JsonParser::new("[1, 2".chars())
+        .parse_into(&mut v)
+        .unwrap_err();
+    assert_eq!((err.line(), err.column()), (1, 5));
+
+    let err = JsonParser::new(r#"{1: 2}"#.chars())
+        .parse_into(&mut v)
+        .unwrap_err();
+    assert!(format!("{}", err).contains("Key of object must be string"));
+}
+
+#[test]
+fn test_parse_interned() {
+    let mut keys = KeyInterner::new();
+    for input in [
+        r#"[{"id": 1, "name": "a"}, {"id": 2, "name": "b", "tags": []}]"#,
+        r#"{"a": {"long_key_name": {"\u3042\t": [1, null]}}, "b": {}}"#,
+        r#"{"a": 1, "a": 2, "b": 3}"#,
+        r#""hello""#,
+        r#"[]"#,
+        r#"42"#,
+    ] {
+        let expected: JsonValue = input.parse().unwrap();
+        let parsed = JsonParser::new(input.chars())
+            .parse_interned(&mut keys)
+            .unwrap();
+        assert_eq!(JsonValue::from(parsed), expected, "input: {:?}", input);
+    }
+    assert_eq!(keys.len(), 7);
+
+    // Objects share the same key allocation
+    let v = JsonParser::new(r#"[{"id": 1}, {"id": 2}]"#.chars())
+        .parse_interned(&mut keys)
+        .unwrap();
+    assert_eq!(v[0]["id"], InternedJsonValue::Number(1.0));
+    let ptrs: Vec<_> = (0..2)
+        .map(|i| match &v[i] {
+            InternedJsonValue::Object(m) => m.keys().next().unwrap().as_ptr(),
+            v => panic!("not an object: {:?}", v),
+        })
+        .collect();
+    assert_eq!(ptrs[0], ptrs[1]);
+    assert_eq!(keys.len(), 7);
+
+    // Parser options are effective
+    let err = JsonParser::new(r#"{"n": 12345678901234567891}"#.chars())
+        .strict_numbers(true)
+        .parse_interned(&mut keys)
+        .unwrap_err();
+    assert_eq!(err.kind(), JsonParseErrorKind::NumberPrecisionLoss);
+
+    for input in [
+        r#"{"a": 1"#,
+        r#"[1 2]"#,
+        r#"{"a" 1}"#,
+        r#"{1: 2}"#,
+        r#"[] 1"#,
+    ] {
+        let err = JsonParser::new(input.chars())
+            .parse_interned(&mut keys)
+            .unwrap_err();
+        let expected = input.parse::<JsonValue>().unwrap_err();
+        assert_eq!(err.to_string(), expected.to_string(), "input: {:?}", input);
+    }
+}
+
+#[test]
+fn test_json_lines_parallel() {
+    let mut input = String::new();
+    for i in 0..1000 {
+        match i % 100 {
+            10 => input.push('\n'),
+            20 => input.push_str("  \r\n"),
+            50 => input.push_str(&format!("{{\"id\": {}\n", i)),
+            _ => input.push_str(&format!("{{\"id\": {}, \"tags\": [\"a\", \"b\"]}}\r\n", i)),
+        }
+    }
+
+    let expected = JsonLinesParser::new().threads(1).parse(input.as_bytes());
+    assert_eq!(expected.len(), 980);
+    for (line, result) in &expected {
+        let i = line - 1;
+        match result {
+            Ok(v) => assert_eq!(v["id"], JsonValue::from(i as f64), "line={}", line),
+            Err(e) => {
+                assert_eq!(i % 100, 50, "line={}", line);
+                assert_eq!(e.line(), *line);
+            }
+        }
+    }
+    let errors: Vec<_> = expected
+        .iter()
+        .filter_map(|(_, r)| r.as_ref().err())
+        .map(|e| e.line())
+        .collect();
+    assert_eq!(errors, (0..10).map(|i| i * 100 + 51).collect::<Vec<_>>());
+
+    for threads in [0, 2, 3, 8, 2000] {
+        let results = JsonLinesParser::new()
+            .threads(threads)
+            .parse(input.as_bytes());
+        assert_eq!(results.len(), expected.len(), "threads={}", threads);
+        for ((line, actual), (expected_line, expected)) in results.iter().zip(expected.iter()) {
+            assert_eq!(line, expected_line, "threads={}", threads);
+            match (actual, expected) {
+                (Ok(a), Ok(e)) => assert_eq!(a, e, "threads={}", threads),
+                (Err(a), Err(e)) => assert_eq!(a.to_string(), e.to_string(), "threads={}", threads),
+                _ => panic!("{:?} v.s. {:?} (threads={})", actual, expected, threads),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_json_lines_invalid_utf8() {
+    let results = JsonLinesParser::new()
+        .threads(2)
+        .parse(b"1\n2\n\"\xff\"\n4");
+    assert_eq!(results.len(), 4);
+    let err = results[2].1.as_ref().unwrap_err();
+    assert_eq!((err.line(), err.column()), (3, 2));
+    assert_eq!(results[3].0, 4);
+    assert!(results[3].1.is_ok());
+    assert!(JsonLinesParser::new().parse(b"").is_empty());
+}
+
+#[test]
+fn test_strict_numbers() {
+    let parse = |s: &str| JsonParser::new(s.chars()).strict_numbers(true).parse();
+
+    for (input, expected) in [
+        ("0", 0.0),
+        ("-0", -0.0),
+        ("0.1", 0.1),
+        ("1e-1", 0.1),
+        ("0e-400", 0.0),
+        ("0.000e999", 0.0),
+        ("5e-324", 5e-324),
+        ("1.7976931348623157e-308", 1.7976931348623157e-308),
+        ("9007199254740992", 9007199254740992.0),
+        ("-9007199254740992", -9007199254740992.0),
+        ("999999999999999", 999999999999999.0),
+        ("18446744073709551616", 18446744073709551616.0),
+        ("99999999999999991611392", 1e23),
+        ("1e22", 1e22),
+        ("9007199254740992e0", 9007199254740992.0),
+        ("9007199254740992.000", 9007199254740992.0),
+        ("90071992547409.92e2", 9007199254740992.0),
+        ("90071992547409920e-1", 9007199254740992.0),
+        ("0.0009007199254740992e19", 9007199254740992.0),
+        ("1234567890123456.5", 1234567890123456.5),
+        ("12345678901234567.8e-3", 12345678901234.568),
+    ] {
+        let v = parse(input).unwrap();
+        assert_eq!(v, JsonValue::Number(expected), "input: {}", input);
+    }
+
+    for (input, kind) in [
+        ("1e400", JsonParseErrorKind::NumberOverflow),
+        ("-1.5e309", JsonParseErrorKind::NumberOverflow),
+        ("1e-400", JsonParseErrorKind::NumberUnderflow),
+        ("-0.0001e-325", JsonParseErrorKind::NumberUnderflow),
+        ("9007199254740993", JsonParseErrorKind::NumberPrecisionLoss),
+        (
+            "-12345678901234567890",
+            JsonParseErrorKind::NumberPrecisionLoss,
+        ),
+        (
+            "100000000000000000000000",
+            JsonParseErrorKind::NumberPrecisionLoss,
+        ),
+        (
+            "[1, 2, {\"x\": 9007199254740993}]",
+            JsonParseErrorKind::NumberPrecisionLoss,
+        ),
+        ("1e23", JsonParseErrorKind::NumberPrecisionLoss),
+        (
+            "90071992547409930e-1",
+            JsonParseErrorKind::NumberPrecisionLoss,
+        ),
+        (
+            "900719925474099300e-2",
+            JsonParseErrorKind::NumberPrecisionLoss,
+        ),
+        (
+            "1.7976931348623157e308",
+            JsonParseErrorKind::NumberPrecisionLoss,
+        ),
+        (
+            "12345678901234567891e0",
+            JsonParseErrorKind::NumberPrecisionLoss,
+        ),
+        (
+            "123456789012345678901234567890.0",
+            JsonParseErrorKind::NumberPrecisionLoss,
+        ),
+        (
+            "9007199254740993.000",
+            JsonParseErrorKind::NumberPrecisionLoss,
+        ),
+        (
+            "90071992547409.93e2",
+            JsonParseErrorKind::NumberPrecisionLoss,
+        ),
+        (
+            "-0.09007199254740993e17",
+            JsonParseErrorKind::NumberPrecisionLoss,
+        ),
+    ] {
+        let err = parse(input).unwrap_err();
+        assert_eq!(err.kind(), kind, "input: {}, error: {}", input, err);
+    }
+
+    // Numbers are rounded silently by default
+    let v: JsonValue = "9007199254740993".parse().unwrap();
+    assert_eq!(v, JsonValue::Number(9007199254740992.0));
+    let v: JsonValue = "1e400".parse().unwrap();
+    assert_eq!(v, JsonValue::Number(f64::INFINITY));
+}
+
+#[test]
+fn test_error_kind() {
+    let err = "[1, 2".parse::<JsonValue>().unwrap_err();
+    assert_eq!(err.kind(), JsonParseErrorKind::Syntax);
+    let err = parse_bytes(b"[\xff]").unwrap_err();
+    assert_eq!(err.kind(), JsonParseErrorKind::Encoding);
+}
+
+#[test]
+fn test_array_stream() {
+    let input = r#"[1, "foo", {"a": [true]}, null]"#;
+    let elems: Vec<_> = JsonArrayStream::new(input.as_bytes(), &[])
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let expected: JsonValue = input.parse().unwrap();
+    assert_eq!(JsonValue::Array(elems), expected);
+
+    let input = r#"
+    {
+        "version": 1,
+        "data": {"meta": [1, 2], "items": [{"id": 1}, {"id": 2}, {"id": 3}], "total": 3},
+        "done": true
+    }
+    "#;
+    let ids: Vec<f64> = JsonArrayStream::new(input.as_bytes(), &["data", "items"])
+        .map(|v| *v.unwrap()["id"].get::<f64>().unwrap())
+        .collect();
+    assert_eq!(ids, vec![1.0, 2.0, 3.0]);
+
+    for input in &["[]", " [ ] ", r#"{"a": [], "b": 0}"#] {
+        let path: &[&str] = if input.contains('a') { &["a"] } else { &[] };
+        let mut stream = JsonArrayStream::new(input.as_bytes(), path);
+        assert!(stream.next().is_none(), "input: {:?}", input);
+    }
+
+    // Large input which is longer than the internal buffer
+    let input = format!(
+        "[{}\"\u{2764}\u{1f600}\"]",
+        "\"\u{3042}\", 1.5, ".repeat(10000)
+    );
+    let mut count = 0;
+    for elem in JsonArrayStream::new(input.as_bytes(), &[]) {
+        elem.unwrap();
+        count += 1;
+    }
+    assert_eq!(count, 20001);
+}
+
+#[test]
+fn test_array_stream_error() {
+    for (input, path, ok) in &[
+        ("[1, 2", &[][..], 2),
+        ("[1, 2,]", &[][..], 2),
+        ("[1, 2] 3", &[][..], 2),
+        ("[1 2]", &[][..], 1),
+        ("{}", &[][..], 0),
+        ("[1]", &["a"][..], 0),
+        (r#"{"b": []}"#, &["a"][..], 0),
+        (r#"{"a": {}}"#, &["a"][..], 0),
+        (r#"{"a": [1], "b": }"#, &["a"][..], 1),
+        (r#"{"a": [1], "b": 1"#, &["a"][..], 1),
+        (r#"{"a": [1]}}"#, &["a"][..], 1),
+    ] {
+        let mut stream = JsonArrayStream::new(input.as_bytes(), path);
+        for _ in 0..*ok {
+            let elem = stream.next().unwrap();
+            assert!(elem.is_ok(), "input: {:?}, result: {:?}", input, elem);
+        }
+        let err = stream.next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), JsonParseErrorKind::Syntax, "input: {:?}", input);
+        assert!(stream.next().is_none(), "input: {:?}", input);
+    }
+
+    let mut stream = JsonArrayStream::new(&b"[\"a\", \"\xff\"]"[..], &[]);
+    assert!(stream.next().unwrap().is_ok());
+    let err = stream.next().unwrap().unwrap_err();
+    assert_eq!(err.kind(), JsonParseErrorKind::Encoding);
+    assert!(stream.next().is_none());
+
+    struct FailingReader(usize);
+    impl std::io::Read for FailingReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.0 == 0 {
+                return Err(std::io::Error::new(std::io::ErrorKind::Other, "oops"));
+            }
+            self.0 -= 1;
+            buf[..2].copy_from_slice(b"1,");
+            Ok(2)
+        }
+    }
+    let reader = std::io::Read::chain(&b"["[..], FailingReader(3));
+    let mut stream = JsonArrayStream::new(reader, &[]);
+    for _ in 0..3 {
+        assert!(stream.next().unwrap().is_ok());
+    }
+    let err = stream.next().unwrap().unwrap_err();
+    assert_eq!(err.kind(), JsonParseErrorKind::Io);
+    assert!(err.to_string().contains("oops"), "{}", err);
+    assert!(stream.next().is_none());
+}
+
+#[test]
+fn test_allow_non_finite() {
+    let parse = |s: &str| {
+        JsonParser::new(s.chars())
+            .allow_non_finite(true)
+            .strict_numbers(true)
+            .parse()
+    };
+
+    let v = parse(r#"{"a": [NaN, -NaN, Infinity, -Infinity, 1]}"#).unwrap();
+    let a: &Vec<_> = v["a"].get().unwrap();
+    assert!(a[0].get::<f64>().unwrap().is_nan());
+    assert!(a[1].get::<f64>().unwrap().is_nan());
+    assert_eq!(a[2], JsonValue::Number(f64::INFINITY));
+    assert_eq!(a[3], JsonValue::Number(f64::NEG_INFINITY));
+    assert_eq!(a[4], JsonValue::Number(1.0));
+
+    for input in &[
+        "Nan",
+        "Inf",
+        "+Infinity",
+        "-Inf",
+        "infinity",
+        "[NaN",
+        "NaNa",
+    ] {
+        assert!(parse(input).is_err(), "input: {:?}", input);
+    }
+    for input in &["NaN", "Infinity", "-Infinity"] {
+        assert!(input.parse::<JsonValue>().is_err(), "input: {:?}", input);
+    }
+}
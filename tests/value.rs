@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use tinyjson::*;
 
 const STR_OK: &str = r#"
@@ -130,9 +130,9 @@ fn test_get() {
 #[test]
 fn test_get_mut() {
     let mut v = STR_OK.parse::<JsonValue>().unwrap();
-    let m: &mut HashMap<_, _> = v.get_mut().unwrap();
+    let m: &mut BTreeMap<_, _> = v.get_mut().unwrap();
     m.clear();
-    let m: &HashMap<_, _> = v.get().unwrap();
+    let m: &BTreeMap<_, _> = v.get().unwrap();
     assert!(m.is_empty());
 }
 
@@ -156,10 +156,10 @@ fn test_try_into() {
         .unwrap();
     assert_eq!(&v, &[JsonValue::Null, JsonValue::Number(3.0)]);
 
-    let mut m = HashMap::new();
+    let mut m = BTreeMap::new();
     m.insert("a".to_string(), JsonValue::Null);
     m.insert("b".to_string(), JsonValue::Boolean(true));
-    let v: HashMap<_, _> = JsonValue::Object(m.clone()).try_into().unwrap();
+    let v: BTreeMap<_, _> = JsonValue::Object(m.clone()).try_into().unwrap();
     assert_eq!(v, m);
 }
 
@@ -182,7 +182,7 @@ fn test_is_xxx() {
     assert!(Array(vec![]).is_array());
     assert!(!Number(1.0).is_array());
 
-    assert!(Object(HashMap::new()).is_object());
+    assert!(Object(BTreeMap::new()).is_object());
     assert!(!Number(1.0).is_object());
 }
 
@@ -255,7 +255,7 @@ fn test_from() {
     assert_eq!(JsonValue::from(()), JsonValue::Null);
     let v = vec![JsonValue::Number(1.0), JsonValue::Boolean(false)];
     assert_eq!(JsonValue::from(v.clone()), JsonValue::Array(v));
-    let m: HashMap<_, _> = [
+    let m: BTreeMap<_, _> = [
         ("a".to_string(), JsonValue::Number(1.0)),
         ("b".to_string(), JsonValue::Boolean(false)),
     ]
@@ -268,3 +268,89 @@ fn test_from() {
     let o = JsonValue::Object([kv("a", 1.0), kv("b", false)].into());
     assert_eq!(JsonValue::Object(m), o);
 }
+
+#[test]
+fn test_summary() {
+    use std::convert::TryFrom;
+
+    let v: JsonValue = r#"
+    {
+      "a": [[1, [2, [3]]], "あいうえお", "esc\"ape", {"x": {"y": {}}}]
+    }
+    "#
+    .parse()
+    .unwrap();
+    assert_eq!(
+        v.summary().to_string(),
+        r#"{"a":[[1,[… 2 more]],"あいうえお","esc\"ape",{"x":{… 1 more}}]}"#,
+    );
+    assert_eq!(
+        v.summary().max_depth(10).max_string_len(2).to_string(),
+        r#"{"a":[[1,[2,[3]]],"あい…","es…",{"x":{"y":{}}}]}"#,
+    );
+    assert_eq!(v.summary().max_depth(0).to_string(), "{… 1 more}");
+    assert_eq!(v["a"].summary().max_items(0).to_string(), "[… 4 more]");
+    assert_eq!(
+        v["a"].summary().max_items(1).to_string(),
+        "[[1,… 1 more],… 3 more]"
+    );
+    assert_eq!(v.summary().max_chars(8).to_string(), r#"{"a":[[1…"#);
+    assert_eq!(v.summary().max_chars(0).to_string(), "…");
+
+    let v = JsonValue::from(vec![f64::NAN.into(), "x".repeat(100).into()]);
+    assert_eq!(v.summary().max_string_len(3).to_string(), r#"[NaN,"xxx…"]"#);
+    assert_eq!(JsonValue::Null.summary().max_chars(4).to_string(), "null");
+
+    // Error messages contain the summary instead of the whole value
+    let v = JsonValue::from(vec![JsonValue::Null; 10000]);
+    let err = String::try_from(v.clone()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Unexpected JSON value: [null,null,null,null,null,null,null,null,… 9992 more]. Expected String value",
+    );
+    let msg = std::panic::catch_unwind(|| {
+        let _ = &v["key"];
+    })
+    .unwrap_err();
+    assert_eq!(
+        msg.downcast_ref::<String>().unwrap(),
+        "Attempted to access to an object with key 'key' but actually it was [null,null,null,null,null,null,null,null,… 9992 more]",
+    );
+}
+
+#[test]
+fn test_serialized_len() {
+    for input in [
+        "null",
+        "[]",
+        "{}",
+        r#""\u0000\u001f\"\\/\b\f\n\r\t  あいう 🐶""#,
+        "[0, -0, 1.5, -1e-7, 1e21, 123456789012345680000, 5e-324, 1.7976931348623157e308]",
+        r#"{"a": {"b": [true, false, null, {"c": [], "d": {}}]}, "é": "ü"}"#,
+        r#"[{"キー": ["値", {"🐶": "\u00e9\u3042"}]}, [[["ü", {"ß": []}]]]]"#,
+    ] {
+        let v: JsonValue = input.parse().unwrap();
+        assert_eq!(
+            v.serialized_len().unwrap(),
+            v.stringify().unwrap().len(),
+            "{}",
+            input
+        );
+        assert_eq!(
+            v.formatted_len("  ").unwrap(),
+            v.format().unwrap().len(),
+            "{}",
+            input
+        );
+        let mut buf = vec![];
+        JsonGenerator::new(&mut buf)
+            .indent("\t\t")
+            .generate(&v)
+            .unwrap();
+        assert_eq!(v.formatted_len("\t\t").unwrap(), buf.len(), "{}", input);
+    }
+
+    let v = JsonValue::from(vec![f64::NAN.into()]);
+    assert!(v.serialized_len().is_err());
+    assert!(v.formatted_len("  ").is_err());
+}